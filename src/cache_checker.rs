@@ -1,17 +1,48 @@
-use crate::http_client::fetch_headers;
-use crate::utils::print_results;
+use crate::cache_control::{self, CacheDirectives, TargetedDirectives, DEFAULT_TARGET_LIST};
+use crate::cache_status::{self, CacheStatusEntry};
+use crate::cdn::{x_cache_verdict, CacheTier, CdnProvider, CdnRegistry, EdgeLocation, AKAMAI_DEBUG_PRAGMA};
+use crate::cacheability::{browser_cacheability, shared_cacheability, Cacheability};
+use crate::freshness::{self, FreshnessSource};
+use crate::credentials::{self, CredentialImpact};
+use crate::error::{ErrorKind, ScanError};
+use crate::fan_out::{self, FanOutReport, FanOutTargets};
+use crate::http_client::{FetchedHeaders, RequestMethod, SniperClient};
+use crate::method_comparison::MethodComparison;
+use crate::origin_comparison::OriginComparison;
+use crate::query_key::{self, QueryKeyAnalysis};
+use crate::redirects::RedirectHop;
+use crate::timing::Timings;
+use crate::utils::{print_fan_out, print_results, print_warmup};
+use crate::vary::{self, VaryAnalysis, VaryProbe};
+use crate::verdict::{CacheStatus, Verdict};
+use crate::warmup::{self, WarmupPlan, WarmupReport};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, AUTHORIZATION, PRAGMA};
+use reqwest::Url;
 use serde::Serialize;
+use std::collections::BTreeMap;
 use std::error::Error;
-use std::fmt;
+use std::net::IpAddr;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 #[derive(Serialize)]
 pub struct CacheResult {
     pub url: String,
+    pub request_method: RequestMethod,
+    pub redirect_chain: Vec<RedirectHop>, // Every hop, final response last; empty unless traced
     pub cache_control: String,
+    pub directives: CacheDirectives,
+    pub targeted_cache_control: Vec<TargetedDirectives>, // CDN-Cache-Control, Surrogate-Control, ...
+    pub edge_ttl_header: String,          // Field that governs the shared-cache policy
+    pub surrogate_keys: Vec<String>,      // Fastly Surrogate-Key
     pub etag: String,
     pub last_modified: String,
     pub expires: String,
     pub cdn_provider: String,
+    pub cdn_details: BTreeMap<String, String>,  // Provider-specific headers
+    pub cdn_request_id: Option<String>,   // CF-Ray, X-Amz-Cf-Id, ...
+    pub edge_location: Option<EdgeLocation>,
+    pub cache_tiers: Vec<CacheTier>,      // Shield → edge, for multi-tier CDNs
+    pub cache_status_layers: Vec<CacheStatusEntry>, // RFC 9211 Cache-Status, origin → client
     // CDN-specific headers
     pub cf_cache_status: Option<String>,  // Cloudflare
     pub cf_ray: Option<String>,           // Cloudflare request ID
@@ -19,75 +50,197 @@ pub struct CacheResult {
     pub x_served_by: Option<String>,      // Fastly
     pub age: Option<String>,              // Generic cache age
     pub x_cache_hits: Option<String>,     // Cache hit count
+    pub x_check_cacheable: Option<String>, // Akamai (debug pragma)
+    pub x_cache_key: Option<String>,      // Akamai (debug pragma)
+    pub x_true_cache_key: Option<String>, // Akamai (debug pragma)
+    // Freshness (RFC 9111 §4.2)
+    pub freshness_lifetime_secs: Option<u64>,
+    pub freshness_source: Option<FreshnessSource>,
+    pub current_age_secs: u64,
+    pub ttl_remaining_secs: Option<i64>,
+    pub is_fresh: bool,
+    // Cacheability per cache class
+    pub browser_cache: Cacheability,
+    pub shared_cache: Cacheability,
+    pub vary_analysis: Option<VaryAnalysis>, // None without a Vary header
+    pub query_key: Option<QueryKeyAnalysis>, // Query string variants vs the cache key, when requested
+    pub method_comparison: Option<MethodComparison>, // HEAD vs GET, when requested
+    pub credential_impact: Option<CredentialImpact>, // Only when Authorization or Cookie was sent
+    pub origin_comparison: Option<OriginComparison>, // Edge vs a direct origin request
+    pub fan_out: Option<FanOutReport>,    // Same URL through every edge address
+    pub warmup: Option<WarmupReport>,     // Sequential requests until the edge serves a HIT
+    pub probe_warnings: Vec<String>,      // Optional probes that failed; the scan itself still stands
     // Cache verdict
     pub is_cached: bool,
-    pub cache_verdict: String,
+    pub cache_status: CacheStatus,
+    pub cache_verdict: String,            // Human explanation of `cache_status`
+    pub timings: Timings,                 // TTFB and total in ms, plus DNS/connect/TLS of a probe connection
 }
 
-/// Custom error wrapper for consistent error handling
-#[derive(Debug)]
-struct CacheSniperError(String);
+/// Settings shared by every scan
+#[derive(Clone, Default)]
+pub struct ScanOptions {
+    /// Client shared by every request of every scan
+    pub client: SniperClient,
+    /// Method used for scan requests
+    pub method: RequestMethod,
+    /// Print every response header
+    pub verbose: bool,
+    /// Providers consulted for CDN detection and status decoding
+    pub cdn_registry: CdnRegistry,
+    /// Send Akamai debug pragmas to reveal cache state and cache key
+    pub akamai_debug: bool,
+    /// Re-request with changed values of each Vary field to see which ones the edge keys on
+    pub vary_probe: bool,
+    /// Re-request with random, reordered and UTM query parameters to see if the query is keyed
+    pub query_probe: bool,
+    /// Follow redirects hop by hop and analyze each one
+    pub trace_redirects: bool,
+    /// Also request with HEAD and GET and report differing cache headers
+    pub compare_methods: bool,
+    /// User-supplied request headers, including cookies and credentials
+    pub extra_headers: HeaderMap,
+    /// Also fetch the URL directly from this origin address and diff the cache headers
+    pub origin_ip: Option<IpAddr>,
+    /// Also scan the URL through each of these addresses
+    pub fan_out: Option<FanOutTargets>,
+    /// Send these sequential requests before the scan and track HIT/MISS, Age and latency
+    pub warmup: Option<WarmupPlan>,
+}
+
+impl ScanOptions {
+    /// Headers added to every scan request
+    pub fn request_headers(&self) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        if self.akamai_debug {
+            headers.insert(PRAGMA, HeaderValue::from_static(AKAMAI_DEBUG_PRAGMA));
+        }
+        for (name, value) in &self.extra_headers {
+            headers.append(name, value.clone());
+        }
+        headers
+    }
+}
+
+/// Directives and headers a shared cache obeys, plus the field they came from.
+/// A targeted field replaces Cache-Control and Expires for the edge (RFC 9213).
+fn edge_policy(
+    headers: &HeaderMap,
+    directives: &CacheDirectives,
+    targeted: &[TargetedDirectives],
+    provider: Option<&dyn CdnProvider>,
+) -> (CacheDirectives, HeaderMap, String) {
+    let target_list = provider.map(|p| p.target_list()).unwrap_or(&DEFAULT_TARGET_LIST);
+    match cache_control::select_targeted(targeted, target_list) {
+        Some(targeted) => {
+            let mut edge_headers = headers.clone();
+            edge_headers.remove("expires");
+            (targeted.directives.clone(), edge_headers, targeted.header.clone())
+        }
+        None => (directives.clone(), headers.clone(), "cache-control".to_string()),
+    }
+}
+
+/// Full verdict for a response, as `check_cache` would report it
+pub fn response_verdict(headers: &HeaderMap, registry: &CdnRegistry, request_has_authorization: bool) -> Verdict {
+    let provider = registry.detect(headers);
+    let cdn_provider = provider.map(|p| p.name().to_string()).unwrap_or_else(|| detect_cdn(headers));
+    let directives = CacheDirectives::from_headers(headers, "cache-control").unwrap_or_default();
+    let targeted = cache_control::targeted_fields(headers);
+    let (edge_directives, edge_headers, _) = edge_policy(headers, &directives, &targeted, provider);
+    let x_cache = headers.get("x-cache").and_then(|v| v.to_str().ok()).map(str::to_string);
+
+    determine_cache_verdict(
+        &cache_status::from_headers(headers),
+        provider,
+        headers,
+        &x_cache,
+        &browser_cacheability(&directives, headers),
+        &shared_cacheability(&edge_directives, &edge_headers, request_has_authorization),
+        &cdn_provider,
+    )
+}
 
-impl fmt::Display for CacheSniperError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.0)
+/// Cache-relevant response headers compared between two responses for the same URL
+pub const COMPARED_HEADERS: [&str; 8] = [
+    "cache-control",
+    "cdn-cache-control",
+    "surrogate-control",
+    "expires",
+    "etag",
+    "last-modified",
+    "vary",
+    "content-type",
+];
+
+/// Every value of header `name` joined with ", ", or `None` when it is absent
+pub fn header_text(headers: &HeaderMap, name: &str) -> Option<String> {
+    let values: Vec<&str> = headers.get_all(name).iter().filter_map(|v| v.to_str().ok()).collect();
+    (!values.is_empty()).then(|| values.join(", "))
+}
+
+/// Status reported by the caches themselves, without falling back to header analysis
+fn reported_verdict(
+    cache_status_layers: &[CacheStatusEntry],
+    provider: Option<&dyn CdnProvider>,
+    headers: &HeaderMap,
+    x_cache: Option<&str>,
+    cdn_provider: &str,
+) -> Option<Verdict> {
+    // The standardized Cache-Status header wins over vendor heuristics
+    if let Some(verdict) = cache_status::verdict(cache_status_layers) {
+        return Some(verdict);
     }
+
+    // Provider-specific decoding
+    if let Some(verdict) = provider.and_then(|p| p.cache_status(headers)) {
+        return Some(verdict);
+    }
+
+    // Generic X-Cache logic for CDNs without a dedicated decoder
+    x_cache.and_then(|v| x_cache_verdict(v, cdn_provider))
 }
 
-impl Error for CacheSniperError {}
+/// Status of a follow-up response, used to compare it with other requests: what the
+/// caches reported or, when they report nothing, whether a shared cache may not store
+/// it at all. `None` when nothing tells whether the response came out of a cache.
+pub fn observed_verdict(headers: &HeaderMap, registry: &CdnRegistry, request_has_authorization: bool) -> Option<Verdict> {
+    let verdict = response_verdict(headers, registry, request_has_authorization);
+    (verdict.status != CacheStatus::Cacheable).then_some(verdict)
+}
 
 /// Determines cache verdict based on CDN-specific headers and standard cache headers
 fn determine_cache_verdict(
-    cf_cache_status: &Option<String>,
+    cache_status_layers: &[CacheStatusEntry],
+    provider: Option<&dyn CdnProvider>,
+    headers: &HeaderMap,
     x_cache: &Option<String>,
-    cache_control: &str,
-    etag: &str,
+    browser_cache: &Cacheability,
+    shared_cache: &Cacheability,
     cdn_provider: &str,
-) -> (bool, String) {
-    // Cloudflare-specific logic
-    if let Some(cf_status) = cf_cache_status {
-        let status_lower = cf_status.to_lowercase();
-        return match status_lower.as_str() {
-            "hit" => (true, "HIT - Served from Cloudflare cache".to_string()),
-            "miss" => (false, "MISS - Origin fetch, not cached yet".to_string()),
-            "expired" => (false, "EXPIRED - Cache entry stale, revalidating".to_string()),
-            "stale" => (true, "STALE - Serving stale content while revalidating".to_string()),
-            "bypass" => (false, "BYPASS - Cache rules bypassed".to_string()),
-            "revalidated" => (true, "REVALIDATED - Cache validated with origin".to_string()),
-            "updating" => (true, "UPDATING - Serving cached while updating".to_string()),
-            "dynamic" => (false, "DYNAMIC - Content marked as uncacheable".to_string()),
-            _ => (false, format!("Unknown Cloudflare status: {}", cf_status)),
-        };
-    }
-
-    // Fastly/generic X-Cache logic
-    if let Some(x_cache_val) = x_cache {
-        let cache_lower = x_cache_val.to_lowercase();
-        if cache_lower.contains("hit") {
-            return (true, format!("HIT - Served from {} cache", cdn_provider));
-        } else if cache_lower.contains("miss") {
-            return (false, format!("MISS - Not in {} cache", cdn_provider));
-        }
+) -> Verdict {
+    if let Some(verdict) = reported_verdict(cache_status_layers, provider, headers, x_cache.as_deref(), cdn_provider) {
+        return verdict;
     }
 
     // Fallback to standard cache header analysis
-    if cache_control != "None" && !cache_control.contains("no-cache") && !cache_control.contains("no-store") {
-        if etag != "None" || cache_control.contains("max-age") {
-            return (true, "Cacheable - Has cache headers".to_string());
-        }
+    if shared_cache.cacheable {
+        return Verdict::new(CacheStatus::Cacheable, format!("Has cache headers, {}", shared_cache.reason));
+    }
+    if browser_cache.cacheable {
+        return Verdict::new(CacheStatus::Private, format!("Browser only, not CDN-cacheable: {}", shared_cache.reason));
     }
 
-    (false, "Not cached - No cache indicators".to_string())
+    Verdict::new(CacheStatus::Uncacheable, "No cache indicators")
 }
 
-/// Detects known CDNs based on the "Server" header
-/// Detects known CDNs based on various headers dynamically
-fn detect_cdn(headers: &reqwest::header::HeaderMap) -> String {
+/// Detects CDNs without a dedicated decoder by keyword in `Server`, `Via` and `X-CDN-Provider`
+fn detect_cdn(headers: &HeaderMap) -> String {
     let known_cdns = vec![
         "cloudflare", "fastly", "akamai", "cloudfront", "gcore", "bunnycdn",
         "cdn77", "stackpath", "edgecast", "limelight", "quic.cloud", "github",
         "imperva", "sucuri", "cachefly", "jsdelivr", "bootstrapcdn", "keycdn",
-        "maxcdn", "belugacdn", "arvancloud", "google", "stackpathdns"
+        "maxcdn", "belugacdn", "arvancloud", "stackpathdns"
     ];
 
     let server_header = headers.get("server")
@@ -116,12 +269,160 @@ fn detect_cdn(headers: &reqwest::header::HeaderMap) -> String {
     "Unknown".to_string()
 }
 
-pub async fn check_cache(url: &str, verbose: bool) -> Result<CacheResult, Box<dyn Error + Send + Sync>> {
-    let headers = fetch_headers(url)
-        .await
-        .map_err(|e| Box::new(CacheSniperError(e.to_string())) as Box<dyn Error + Send + Sync>)?;
+/// Re-requests `url` once unmodified and once per Vary field with a fresh
+/// value, comparing the reported cache status against the unmodified request
+async fn probe_vary(url: &str, options: &ScanOptions, analysis: &mut VaryAnalysis) {
+    let base_headers = options.request_headers();
+    let has_authorization = base_headers.contains_key(AUTHORIZATION);
+    let baseline = match options.client.fetch_headers(url, options.method, &base_headers).await {
+        Ok(fetched) => observed_verdict(&fetched.headers, &options.cdn_registry, has_authorization),
+        Err(e) => {
+            analysis.warnings.push(format!("Vary probe skipped: {}", e));
+            return;
+        }
+    };
+    let Some(baseline) = baseline else {
+        analysis.warnings.push("Vary probe skipped: the response reports no cache status".to_string());
+        return;
+    };
+    analysis.baseline_status = Some(baseline.status);
+
+    let nonce = format!("{:x}", SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or_default());
+    let fields: Vec<String> = analysis.probe_fields().map(str::to_string).collect();
+    for field in fields {
+        let sent_value = vary::probe_value(&field, &nonce);
+        let (Ok(name), Ok(value)) = (HeaderName::from_bytes(field.as_bytes()), HeaderValue::from_str(&sent_value)) else {
+            continue;
+        };
+        let mut request_headers = base_headers.clone();
+        request_headers.insert(name, value);
+
+        match options.client.fetch_headers(url, options.method, &request_headers).await {
+            Ok(fetched) => {
+                let status = observed_verdict(&fetched.headers, &options.cdn_registry, has_authorization)
+                    .map(|v| v.status)
+                    .unwrap_or(CacheStatus::Unknown);
+                let keyed = vary::keyed(baseline.status, status);
+                analysis.probes.push(VaryProbe { header: field, sent_value, status, keyed });
+            }
+            Err(e) => analysis.warnings.push(format!("Vary probe for {} failed: {}", field, e)),
+        }
+    }
+}
+
+/// Requests `url` with HEAD and GET and compares the cache-relevant headers
+async fn compare_methods(url: &str, options: &ScanOptions) -> Result<MethodComparison, Box<dyn Error + Send + Sync>> {
+    let request_headers = options.request_headers();
+    let (head, get) = tokio::join!(
+        options.client.fetch_headers(url, RequestMethod::Head, &request_headers),
+        options.client.fetch_headers(url, RequestMethod::Get, &request_headers),
+    );
+    let (head, get) = (head?, get?);
+    Ok(MethodComparison::compare(
+        head.status.as_u16(),
+        &head.headers,
+        get.status.as_u16(),
+        &get.headers,
+        &options.cdn_registry,
+        request_headers.contains_key(AUTHORIZATION),
+    ))
+}
+
+/// Repeats the request without Authorization and Cookie to see whether they change the verdict
+async fn credential_impact(
+    url: &str,
+    options: &ScanOptions,
+    with_credentials: CacheStatus,
+) -> Result<Option<CredentialImpact>, Box<dyn Error + Send + Sync>> {
+    let request_headers = options.request_headers();
+    let sent = credentials::sent_credentials(&request_headers);
+    if sent.is_empty() {
+        return Ok(None);
+    }
+
+    let anonymous = options.client.fetch_headers(url, options.method, &credentials::anonymous(&request_headers)).await?;
+    let verdict = response_verdict(&anonymous.headers, &options.cdn_registry, false);
+    Ok(Some(CredentialImpact::new(sent, with_credentials, verdict.status, verdict.reason)))
+}
+
+/// Fetches `url` directly from `origin_ip`, keeping the Host and TLS name of the URL
+async fn compare_origin(
+    url: &str,
+    origin_ip: IpAddr,
+    edge: &FetchedHeaders,
+    options: &ScanOptions,
+) -> Result<OriginComparison, Box<dyn Error + Send + Sync>> {
+    let host = Url::parse(url)?.host_str().ok_or("URL has no host")?.to_string();
+    let origin_client = options.client.pinned(&host, &[origin_ip])?;
+    let request_headers = options.request_headers();
+    let origin = origin_client.fetch_headers(url, options.method, &request_headers).await?;
+
+    Ok(OriginComparison::compare(
+        origin_ip,
+        edge.status.as_u16(),
+        &edge.headers,
+        origin.status.as_u16(),
+        &origin.headers,
+        &options.cdn_registry,
+        request_headers.contains_key(AUTHORIZATION),
+    ))
+}
+
+/// Scans `url` through each of `targets`
+async fn scan_addresses(url: &str, targets: &FanOutTargets, options: &ScanOptions) -> Result<FanOutReport, Box<dyn Error + Send + Sync>> {
+    let addrs = fan_out::target_addresses(url, targets).await?;
+    fan_out::fan_out(url, &addrs, &options.client, options.method, &options.request_headers(), &options.cdn_registry).await
+}
+
+fn yes_no(value: bool) -> &'static str {
+    if value { "yes" } else { "no" }
+}
+
+pub async fn check_cache(url: &str, options: &ScanOptions) -> Result<CacheResult, ScanError> {
+    let mut probe_warnings = Vec::new();
+    // Runs first so that its first request is the first visit
+    let warmup = match options.warmup {
+        Some(plan) => {
+            match warmup::warm_up(url, &options.client, options.method, &options.request_headers(), &options.cdn_registry, plan).await {
+                Ok(report) => Some(report),
+                Err(e) => {
+                    probe_warnings.push(format!("Warm-up skipped: {}", e));
+                    None
+                }
+            }
+        }
+        None => None,
+    };
+    let connection_timings = options.client.connection_timings(url).await;
+    let (fetched, redirect_chain) = if options.trace_redirects {
+        let mut hops = options.client.fetch_chain(url, options.method, &options.request_headers()).await?;
+        let has_authorization = options.request_headers().contains_key(AUTHORIZATION);
+        let chain = hops
+            .iter()
+            .map(|hop| RedirectHop::analyze(&hop.url, hop.status.as_u16(), &hop.headers, &options.cdn_registry, has_authorization))
+            .collect::<Vec<_>>();
+        let last = hops.pop().ok_or_else(|| ScanError::new(ErrorKind::Other, "Empty redirect chain"))?;
+        (last, chain)
+    } else {
+        let fetched = options.client.fetch_timed(url, options.method, &options.request_headers()).await?;
+        (fetched, Vec::new())
+    };
+    let elapsed = |until: SystemTime| until.duration_since(fetched.request_time).unwrap_or_default();
+    let timings = Timings::new(connection_timings, elapsed(fetched.response_time), elapsed(fetched.completed_time));
+
+    let origin_comparison = match options.origin_ip {
+        Some(origin_ip) => match compare_origin(url, origin_ip, &fetched, options).await {
+            Ok(comparison) => Some(comparison),
+            Err(e) => {
+                probe_warnings.push(format!("Origin comparison with {} skipped: {}", origin_ip, e));
+                None
+            }
+        },
+        None => None,
+    };
+    let headers = fetched.headers;
 
-    if verbose {
+    if options.verbose {
         println!("
 📜 Full Headers for {}:", url);
         for (key, value) in headers.iter() {
@@ -129,13 +430,20 @@ pub async fn check_cache(url: &str, verbose: bool) -> Result<CacheResult, Box<dy
         }
     }
 
-    let cdn_provider = detect_cdn(&headers);
+    let provider = options.cdn_registry.detect(&headers);
+    let cdn_provider = provider.map(|p| p.name().to_string()).unwrap_or_else(|| detect_cdn(&headers));
+    let cdn_details = provider.map(|p| p.extract(&headers)).unwrap_or_default();
+    let cdn_request_id = provider.and_then(|p| p.request_id(&headers));
+    let edge_location = provider.and_then(|p| p.edge_location(&headers));
+    let cache_tiers = provider.map(|p| p.cache_tiers(&headers)).unwrap_or_default();
+    let cache_status_layers = cache_status::from_headers(&headers);
 
     if cdn_provider != "None" {
         println!("\n🌐 CDN Provider: {}", cdn_provider);
     }
 
     let cache_control = headers.get("cache-control").map(|v| v.to_str().unwrap_or("")).unwrap_or("None").to_string();
+    let directives = CacheDirectives::from_headers(&headers, "cache-control").unwrap_or_default();
     let etag = headers.get("etag").map(|v| v.to_str().unwrap_or("")).unwrap_or("None").to_string();
     let last_modified = headers.get("last-modified").map(|v| v.to_str().unwrap_or("")).unwrap_or("None").to_string();
     let expires = headers.get("expires").map(|v| v.to_str().unwrap_or("")).unwrap_or("None").to_string();
@@ -147,17 +455,104 @@ pub async fn check_cache(url: &str, verbose: bool) -> Result<CacheResult, Box<dy
     let x_served_by = headers.get("x-served-by").map(|v| v.to_str().unwrap_or("").to_string());
     let age = headers.get("age").map(|v| v.to_str().unwrap_or("").to_string());
     let x_cache_hits = headers.get("x-cache-hits").map(|v| v.to_str().unwrap_or("").to_string());
+    let x_check_cacheable = headers.get("x-check-cacheable").map(|v| v.to_str().unwrap_or("").to_string());
+    let x_cache_key = headers.get("x-cache-key").map(|v| v.to_str().unwrap_or("").to_string());
+    let x_true_cache_key = headers.get("x-true-cache-key").map(|v| v.to_str().unwrap_or("").to_string());
+
+    let targeted_cache_control = cache_control::targeted_fields(&headers);
+    let (edge_directives, edge_headers, edge_ttl_header) = edge_policy(&headers, &directives, &targeted_cache_control, provider);
+    let surrogate_keys = headers
+        .get_all("surrogate-key")
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split_whitespace().map(str::to_string))
+        .collect::<Vec<_>>();
+
+    let freshness = freshness::evaluate(
+        &edge_directives,
+        &edge_headers,
+        fetched.request_time,
+        fetched.response_time,
+        SystemTime::now(),
+    );
+
+    let browser_cache = browser_cacheability(&directives, &headers);
+    let request_headers = options.request_headers();
+    let shared_cache = shared_cacheability(&edge_directives, &edge_headers, request_headers.contains_key(AUTHORIZATION));
+
+    let mut vary_analysis = VaryAnalysis::from_headers(&headers);
+    if options.vary_probe {
+        if let Some(analysis) = vary_analysis.as_mut() {
+            probe_vary(url, options, analysis).await;
+        }
+    }
+
+    let query_key = if options.query_probe {
+        match query_key::probe_query_key(url, &options.client, options.method, &request_headers, &options.cdn_registry).await {
+            Ok(analysis) => Some(analysis),
+            Err(e) => {
+                probe_warnings.push(format!("Query probe skipped: {}", e));
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    let method_comparison = if options.compare_methods {
+        match compare_methods(url, options).await {
+            Ok(comparison) => Some(comparison),
+            Err(e) => {
+                probe_warnings.push(format!("HEAD vs GET comparison skipped: {}", e));
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    let fan_out = match &options.fan_out {
+        Some(targets) => match scan_addresses(url, targets, options).await {
+            Ok(report) => Some(report),
+            Err(e) => {
+                probe_warnings.push(format!("Per-address scan skipped: {}", e));
+                None
+            }
+        },
+        None => None,
+    };
 
     // Determine cache verdict
-    let (is_cached, cache_verdict) = determine_cache_verdict(
-        &cf_cache_status,
+    let verdict = determine_cache_verdict(
+        &cache_status_layers,
+        provider,
+        &headers,
         &x_cache,
-        &cache_control,
-        &etag,
+        &browser_cache,
+        &shared_cache,
         &cdn_provider,
     );
 
-    print_results(url, &cache_control, &etag, &last_modified, &expires);
+    let credential_impact = match credential_impact(url, options, verdict.status).await {
+        Ok(impact) => impact,
+        Err(e) => {
+            probe_warnings.push(format!("Credential impact check skipped: {}", e));
+            None
+        }
+    };
+
+    print_results(url, &cache_control, &directives, &etag, &last_modified, &expires, &verdict, &timings);
+
+    if redirect_chain.len() > 1 {
+        println!("↪️  Redirect chain:");
+        for hop in &redirect_chain {
+            let status = hop.cache_status.map(|s| s.to_string()).unwrap_or_else(|| "-".to_string());
+            println!("   {} {} (cache: {}, Cache-Control: {})", hop.status, hop.url, status, hop.cache_control.as_deref().unwrap_or("None"));
+            for warning in &hop.warnings {
+                println!("   ⚠️  {}", warning);
+            }
+        }
+    }
 
     // Print CDN-specific info if available
     if let Some(ref status) = cf_cache_status {
@@ -166,26 +561,197 @@ pub async fn check_cache(url: &str, verbose: bool) -> Result<CacheResult, Box<dy
     if let Some(ref cache) = x_cache {
         println!("🔍 X-Cache: {}", cache);
     }
+    if let Some(ref cacheable) = x_check_cacheable {
+        println!("🔍 X-Check-Cacheable: {}", cacheable);
+    }
+    if let Some(ref key) = x_cache_key {
+        println!("🔑 X-Cache-Key: {}", key);
+    }
+    if let Some(ref request_id) = cdn_request_id {
+        println!("🆔 Request ID: {}", request_id);
+    }
+    if let Some(ref location) = edge_location {
+        let place = [location.airport.as_deref(), location.city.as_deref()]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join(", ");
+        println!("📍 Edge location: {} ({})", location.pop, place);
+    }
+    for tier in &cache_tiers {
+        let hits = tier.hits.map(|h| format!(", {} hits", h)).unwrap_or_default();
+        println!("🧱 {} tier {}: {}{}", tier.role, tier.node.as_deref().unwrap_or("?"), tier.status, hits);
+    }
+    if edge_ttl_header != "cache-control" {
+        println!("🎯 Edge policy governed by: {}", edge_ttl_header);
+    }
+    if !surrogate_keys.is_empty() {
+        println!("🏷️  Surrogate-Key: {}", surrogate_keys.join(" "));
+    }
+    for layer in &cache_status_layers {
+        let outcome = if layer.hit { "hit".to_string() } else { format!("fwd={}", layer.fwd.as_deref().unwrap_or("?")) };
+        let ttl = layer.ttl.map(|t| format!(", ttl={}", t)).unwrap_or_default();
+        println!("🧾 Cache-Status {}: {}{}", layer.cache, outcome, ttl);
+    }
     if let Some(ref age_val) = age {
         println!("⏱️  Age: {} seconds", age_val);
     }
+    if let (Some(lifetime), Some(ttl)) = (freshness.lifetime_secs, freshness.ttl_remaining_secs) {
+        let state = if freshness.is_fresh { "fresh" } else { "stale" };
+        println!("🧮 Freshness: lifetime {}s, current age {}s, TTL remaining {}s ({})", lifetime, freshness.current_age_secs, ttl, state);
+    }
+
+    if let Some(ref analysis) = vary_analysis {
+        println!("🔀 Vary: {}", analysis.fields.join(", "));
+        for warning in &analysis.warnings {
+            println!("⚠️  {}", warning);
+        }
+        for probe in &analysis.probes {
+            let keyed = match probe.keyed {
+                Some(true) => "part of the cache key",
+                Some(false) => "ignored by the cache",
+                None => "inconclusive",
+            };
+            println!("🧪 Vary probe {}: {} - {}", probe.header, probe.status, keyed);
+        }
+    }
+
+    if let Some(ref analysis) = query_key {
+        println!("🔗 Query string is part of cache key: {}", analysis.query_in_cache_key);
+        for probe in &analysis.probes {
+            let shared = match probe.shares_entry {
+                Some(true) => "shares the cache entry",
+                Some(false) => "separate cache entry",
+                None => "inconclusive",
+            };
+            println!("🧪 Query probe with {}: {} - {}", probe.variant, probe.observation.status, shared);
+        }
+        for warning in &analysis.warnings {
+            println!("⚠️  {}", warning);
+        }
+    }
+
+    if let Some(ref comparison) = method_comparison {
+        if comparison.consistent {
+            println!("🆚 HEAD vs GET: consistent");
+        } else {
+            let status = |s: Option<CacheStatus>| s.map(|s| s.to_string()).unwrap_or_else(|| "-".to_string());
+            println!(
+                "🆚 HEAD vs GET: {} {} vs {} {}",
+                comparison.head_status,
+                status(comparison.head_cache_status),
+                comparison.get_status,
+                status(comparison.get_cache_status)
+            );
+            for diff in &comparison.differences {
+                println!(
+                    "   ⚠️  {} differs: HEAD {} / GET {}",
+                    diff.header,
+                    diff.head.as_deref().unwrap_or("None"),
+                    diff.get.as_deref().unwrap_or("None")
+                );
+            }
+        }
+    }
+
+    if let Some(ref impact) = credential_impact {
+        if impact.changed {
+            println!(
+                "🔐 Sending {} changed the verdict: {} with credentials, {} without",
+                impact.sent.join(" and "),
+                impact.with_credentials,
+                impact.without_credentials
+            );
+        } else {
+            println!("🔐 Sending {} did not change the verdict ({})", impact.sent.join(" and "), impact.with_credentials);
+        }
+    }
+
+    if let Some(ref comparison) = origin_comparison {
+        println!(
+            "🏠 Origin {}: {} (edge {}), {} differing cache header(s)",
+            comparison.origin_ip,
+            comparison.origin_status,
+            comparison.edge_status,
+            comparison.differences.len()
+        );
+        if comparison.same_object == Some(false) {
+            println!("   ⚠️  Edge and origin validators differ: the edge is serving another version");
+        }
+        for diff in &comparison.differences {
+            println!(
+                "   {}: edge {} / origin {}",
+                diff.header,
+                diff.edge.as_deref().unwrap_or("None"),
+                diff.origin.as_deref().unwrap_or("None")
+            );
+        }
+    }
+
+    if let Some(ref report) = fan_out {
+        println!("🛰️  Per-address results:");
+        print_fan_out(report);
+    }
+
+    if let Some(ref report) = warmup {
+        println!("🔥 Warm-up:");
+        print_warmup(report);
+    }
+
+    for warning in &probe_warnings {
+        println!("⚠️  {}", warning);
+    }
+
+    println!("🖥️  Browser cache: {} - {}", yes_no(browser_cache.cacheable), browser_cache.reason);
+    println!("🌐 Shared cache (CDN/proxy): {} - {}", yes_no(shared_cache.cacheable), shared_cache.reason);
 
-    println!("\n📊 Verdict: {}", cache_verdict);
+    println!("\n📊 Verdict: {}", verdict);
 
     Ok(CacheResult {
         url: url.to_string(),
+        request_method: options.method,
+        redirect_chain,
         cache_control,
+        directives,
+        targeted_cache_control,
+        edge_ttl_header,
+        surrogate_keys,
         etag,
         last_modified,
         expires,
         cdn_provider,
+        cdn_details,
+        cdn_request_id,
+        edge_location,
+        cache_tiers,
+        cache_status_layers,
         cf_cache_status,
         cf_ray,
         x_cache,
         x_served_by,
         age,
         x_cache_hits,
-        is_cached,
-        cache_verdict,
+        x_check_cacheable,
+        x_cache_key,
+        x_true_cache_key,
+        freshness_lifetime_secs: freshness.lifetime_secs,
+        freshness_source: freshness.source,
+        current_age_secs: freshness.current_age_secs,
+        ttl_remaining_secs: freshness.ttl_remaining_secs,
+        is_fresh: freshness.is_fresh,
+        browser_cache,
+        shared_cache,
+        vary_analysis,
+        query_key,
+        method_comparison,
+        credential_impact,
+        origin_comparison,
+        fan_out,
+        warmup,
+        probe_warnings,
+        is_cached: verdict.status.is_cached(),
+        cache_status: verdict.status,
+        cache_verdict: verdict.reason,
+        timings,
     })
 }
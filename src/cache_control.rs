@@ -0,0 +1,232 @@
+use reqwest::header::HeaderMap;
+use serde::Serialize;
+
+/// Value used when a delta-seconds value overflows (RFC 9111 §1.2.2)
+const DELTA_SECONDS_MAX: u64 = 2_147_483_648;
+
+/// Cache-Control style fields aimed at CDNs rather than every cache
+pub const TARGETED_FIELDS: [&str; 4] = [
+    "cloudflare-cdn-cache-control",
+    "akamai-cache-control",
+    "cdn-cache-control",
+    "surrogate-control",
+];
+
+/// Target list used when no provider-specific list applies (RFC 9213 §2.1)
+pub const DEFAULT_TARGET_LIST: [&str; 1] = ["cdn-cache-control"];
+
+/// A directive the parser does not know about, kept verbatim
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CacheExtension {
+    pub name: String,
+    pub value: Option<String>,
+}
+
+/// Typed view of a `Cache-Control` header (RFC 9111 §5.2)
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct CacheDirectives {
+    pub max_age: Option<u64>,
+    pub s_maxage: Option<u64>,
+    pub public: bool,
+    pub private: bool,
+    /// Field names from a qualified `private="..."`
+    pub private_fields: Vec<String>,
+    pub no_store: bool,
+    pub no_cache: bool,
+    /// Field names from a qualified `no-cache="..."`
+    pub no_cache_fields: Vec<String>,
+    pub no_transform: bool,
+    pub must_revalidate: bool,
+    pub proxy_revalidate: bool,
+    pub must_understand: bool,
+    pub immutable: bool,
+    pub stale_while_revalidate: Option<u64>,
+    pub stale_if_error: Option<u64>,
+    pub extensions: Vec<CacheExtension>,
+}
+
+impl CacheDirectives {
+    /// Parses a `Cache-Control` field value.
+    ///
+    /// Directive names are case-insensitive and the first occurrence of a
+    /// duplicated directive wins. Invalid delta-seconds are treated as `0`
+    /// so the response is considered stale, as RFC 9111 §4.2.1 encourages.
+    pub fn parse(value: &str) -> Self {
+        let mut directives = CacheDirectives::default();
+        let mut seen: Vec<String> = Vec::new();
+
+        for item in split_list(value) {
+            let (name, arg) = match item.split_once('=') {
+                Some((n, v)) => (n.trim().to_ascii_lowercase(), Some(unquote(v.trim()))),
+                None => (item.trim().to_ascii_lowercase(), None),
+            };
+            if name.is_empty() {
+                continue;
+            }
+            if seen.contains(&name) {
+                continue;
+            }
+            seen.push(name.clone());
+
+            match name.as_str() {
+                "max-age" => directives.max_age = Some(delta_seconds(arg.as_deref())),
+                "s-maxage" => directives.s_maxage = Some(delta_seconds(arg.as_deref())),
+                "public" => directives.public = true,
+                "private" => {
+                    directives.private = true;
+                    directives.private_fields = field_names(arg.as_deref());
+                }
+                "no-store" => directives.no_store = true,
+                "no-cache" => {
+                    directives.no_cache = true;
+                    directives.no_cache_fields = field_names(arg.as_deref());
+                }
+                "no-transform" => directives.no_transform = true,
+                "must-revalidate" => directives.must_revalidate = true,
+                "proxy-revalidate" => directives.proxy_revalidate = true,
+                "must-understand" => directives.must_understand = true,
+                "immutable" => directives.immutable = true,
+                "stale-while-revalidate" => directives.stale_while_revalidate = Some(delta_seconds(arg.as_deref())),
+                "stale-if-error" => directives.stale_if_error = Some(delta_seconds(arg.as_deref())),
+                _ => directives.extensions.push(CacheExtension { name, value: arg }),
+            }
+        }
+
+        directives
+    }
+
+    /// Parses every occurrence of `name` in `headers` as one combined list
+    pub fn from_headers(headers: &HeaderMap, name: &str) -> Option<Self> {
+        let values: Vec<&str> = headers
+            .get_all(name)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .collect();
+
+        if values.is_empty() {
+            return None;
+        }
+        Some(Self::parse(&values.join(", ")))
+    }
+
+    /// True when no directive at all was present
+    pub fn is_empty(&self) -> bool {
+        *self == CacheDirectives::default()
+    }
+
+    /// True for an unqualified `no-cache`: every reuse must be revalidated.
+    ///
+    /// A qualified `no-cache="Set-Cookie"` only restricts the listed fields
+    /// and does not prevent the response itself from being reused.
+    pub fn requires_revalidation(&self) -> bool {
+        self.no_cache && self.no_cache_fields.is_empty()
+    }
+
+    /// True for an unqualified `private`
+    pub fn is_private(&self) -> bool {
+        self.private && self.private_fields.is_empty()
+    }
+}
+
+/// Directives from one targeted field such as `CDN-Cache-Control`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TargetedDirectives {
+    pub header: String,
+    pub directives: CacheDirectives,
+}
+
+/// Parses every targeted field present in the response
+pub fn targeted_fields(headers: &HeaderMap) -> Vec<TargetedDirectives> {
+    TARGETED_FIELDS
+        .iter()
+        .filter_map(|name| {
+            CacheDirectives::from_headers(headers, name).map(|directives| TargetedDirectives {
+                header: name.to_string(),
+                directives,
+            })
+        })
+        .collect()
+}
+
+/// Picks the field a cache with `target_list` obeys: the first listed field
+/// with a non-empty value. When one is found the cache ignores Cache-Control
+/// and Expires entirely (RFC 9213 §2.2).
+pub fn select_targeted<'a>(fields: &'a [TargetedDirectives], target_list: &[&str]) -> Option<&'a TargetedDirectives> {
+    target_list
+        .iter()
+        .find_map(|name| fields.iter().find(|f| f.header == *name && !f.directives.is_empty()))
+}
+
+/// Splits a comma-separated list, ignoring commas inside quoted strings
+fn split_list(value: &str) -> Vec<String> {
+    let mut items = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut escaped = false;
+
+    for c in value.chars() {
+        if escaped {
+            current.push(c);
+            escaped = false;
+            continue;
+        }
+        match c {
+            '\\' if in_quotes => {
+                current.push(c);
+                escaped = true;
+            }
+            '"' => {
+                current.push(c);
+                in_quotes = !in_quotes;
+            }
+            ',' if !in_quotes => {
+                items.push(current.trim().to_string());
+                current.clear();
+            }
+            _ => current.push(c),
+        }
+    }
+    items.push(current.trim().to_string());
+
+    items.into_iter().filter(|i| !i.is_empty()).collect()
+}
+
+/// Removes surrounding quotes and backslash escapes from a quoted-string
+fn unquote(value: &str) -> String {
+    if value.len() < 2 || !value.starts_with('"') || !value.ends_with('"') {
+        return value.to_string();
+    }
+
+    let mut out = String::new();
+    let mut chars = value[1..value.len() - 1].chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            if let Some(next) = chars.next() {
+                out.push(next);
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// Parses a delta-seconds argument, clamping overflow and zeroing garbage
+fn delta_seconds(arg: Option<&str>) -> u64 {
+    let arg = match arg {
+        Some(a) if !a.is_empty() && a.bytes().all(|b| b.is_ascii_digit()) => a,
+        _ => return 0,
+    };
+    arg.parse::<u64>().unwrap_or(DELTA_SECONDS_MAX).min(DELTA_SECONDS_MAX)
+}
+
+/// Splits the field-name list of a qualified `no-cache` or `private`
+fn field_names(arg: Option<&str>) -> Vec<String> {
+    arg.map(|a| {
+        a.split(',')
+            .map(|f| f.trim().to_ascii_lowercase())
+            .filter(|f| !f.is_empty())
+            .collect()
+    })
+    .unwrap_or_default()
+}
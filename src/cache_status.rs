@@ -0,0 +1,269 @@
+use crate::verdict::{CacheStatus, Verdict};
+use reqwest::header::HeaderMap;
+use serde::Serialize;
+
+/// One cache layer from a `Cache-Status` header (RFC 9211)
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct CacheStatusEntry {
+    /// Name of the cache that added the entry
+    pub cache: String,
+    pub hit: bool,
+    /// Why the request was forwarded (`uri-miss`, `stale`, `bypass`, ...)
+    pub fwd: Option<String>,
+    pub fwd_status: Option<u16>,
+    /// Remaining freshness lifetime in seconds; negative when stale
+    pub ttl: Option<i64>,
+    pub stored: bool,
+    pub collapsed: bool,
+    pub key: Option<String>,
+    pub detail: Option<String>,
+}
+
+/// Structured-field bare item (RFC 8941 §3.3)
+#[derive(Debug, Clone, PartialEq)]
+enum BareItem {
+    Str(String),
+    Token(String),
+    Int(i64),
+    Decimal(f64),
+    Bool(bool),
+}
+
+impl BareItem {
+    fn as_text(&self) -> Option<String> {
+        match self {
+            BareItem::Str(s) | BareItem::Token(s) => Some(s.clone()),
+            BareItem::Int(i) => Some(i.to_string()),
+            BareItem::Decimal(d) => Some(d.to_string()),
+            BareItem::Bool(_) => None,
+        }
+    }
+
+    fn as_int(&self) -> Option<i64> {
+        match self {
+            BareItem::Int(i) => Some(*i),
+            BareItem::Decimal(d) => Some(*d as i64),
+            _ => None,
+        }
+    }
+
+    fn is_true(&self) -> bool {
+        matches!(self, BareItem::Bool(true))
+    }
+}
+
+/// Parses a `Cache-Status` field value into its layers.
+///
+/// Layers are returned in header order: the first is the cache closest to
+/// the origin, the last the cache closest to the client. Malformed members
+/// are skipped rather than failing the whole header.
+pub fn parse(value: &str) -> Vec<CacheStatusEntry> {
+    let mut parser = Parser { input: value.as_bytes(), pos: 0 };
+    let mut entries = Vec::new();
+
+    loop {
+        parser.skip_whitespace();
+        if parser.at_end() {
+            break;
+        }
+        if let Some(entry) = parser.member() {
+            entries.push(entry);
+            parser.skip_whitespace();
+            if parser.eat(b',') {
+                continue;
+            }
+        }
+        parser.skip_past_comma();
+    }
+
+    entries
+}
+
+/// Parses every `Cache-Status` line in `headers` as one combined list
+pub fn from_headers(headers: &HeaderMap) -> Vec<CacheStatusEntry> {
+    let values: Vec<&str> = headers
+        .get_all("cache-status")
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .collect();
+    parse(&values.join(", "))
+}
+
+/// Derives a verdict from the layer closest to the client
+pub fn verdict(entries: &[CacheStatusEntry]) -> Option<Verdict> {
+    let layer = entries.last()?;
+    let name = &layer.cache;
+
+    if layer.hit {
+        return Some(match layer.ttl {
+            Some(ttl) if ttl < 0 => Verdict::new(CacheStatus::Stale, format!("Cache-Status: {} served a stale hit (ttl={})", name, ttl)),
+            Some(ttl) => Verdict::new(CacheStatus::Hit, format!("Cache-Status: {} hit (ttl={})", name, ttl)),
+            None => Verdict::new(CacheStatus::Hit, format!("Cache-Status: {} hit", name)),
+        });
+    }
+
+    let fwd = layer.fwd.as_deref()?;
+    let verdict = match fwd {
+        "stale" if layer.fwd_status == Some(304) => {
+            Verdict::new(CacheStatus::Revalidated, format!("Cache-Status: {} revalidated a stale entry (304)", name))
+        }
+        "stale" => Verdict::new(CacheStatus::Expired, format!("Cache-Status: {} had a stale entry and forwarded", name)),
+        "bypass" | "method" | "request" => {
+            Verdict::new(CacheStatus::Bypass, format!("Cache-Status: {} bypassed the cache (fwd={})", name, fwd))
+        }
+        "uri-miss" | "vary-miss" | "miss" | "partial" => {
+            Verdict::new(CacheStatus::Miss, format!("Cache-Status: {} missed (fwd={})", name, fwd))
+        }
+        other => Verdict::new(CacheStatus::Unknown, format!("Cache-Status: {} forwarded (fwd={})", name, other)),
+    };
+    Some(verdict)
+}
+
+struct Parser<'a> {
+    input: &'a [u8],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn at_end(&self) -> bool {
+        self.pos >= self.input.len()
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.input.get(self.pos).copied()
+    }
+
+    fn eat(&mut self, byte: u8) -> bool {
+        if self.peek() == Some(byte) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(b' ') | Some(b'\t')) {
+            self.pos += 1;
+        }
+    }
+
+    fn skip_past_comma(&mut self) {
+        let mut in_string = false;
+        while let Some(c) = self.peek() {
+            self.pos += 1;
+            match c {
+                b'\\' if in_string => self.pos += 1,
+                b'"' => in_string = !in_string,
+                b',' if !in_string => return,
+                _ => {}
+            }
+        }
+    }
+
+    /// Parses `cache-name *( ";" key [ "=" value ] )`
+    fn member(&mut self) -> Option<CacheStatusEntry> {
+        let cache = self.bare_item()?.as_text()?;
+        let mut entry = CacheStatusEntry { cache, ..CacheStatusEntry::default() };
+
+        loop {
+            self.skip_whitespace();
+            if !self.eat(b';') {
+                break;
+            }
+            self.skip_whitespace();
+            let key = self.key()?;
+            let value = if self.eat(b'=') { self.bare_item()? } else { BareItem::Bool(true) };
+
+            match key.as_str() {
+                "hit" => entry.hit = value.is_true(),
+                "fwd" => entry.fwd = value.as_text(),
+                "fwd-status" => entry.fwd_status = value.as_int().and_then(|s| u16::try_from(s).ok()),
+                "ttl" => entry.ttl = value.as_int(),
+                "stored" => entry.stored = value.is_true(),
+                "collapsed" => entry.collapsed = value.is_true(),
+                "key" => entry.key = value.as_text(),
+                "detail" => entry.detail = value.as_text(),
+                _ => {}
+            }
+        }
+
+        Some(entry)
+    }
+
+    fn key(&mut self) -> Option<String> {
+        let start = self.pos;
+        while let Some(c) = self.peek() {
+            if c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, b'_' | b'-' | b'.' | b'*') {
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+        (self.pos > start).then(|| String::from_utf8_lossy(&self.input[start..self.pos]).into_owned())
+    }
+
+    fn bare_item(&mut self) -> Option<BareItem> {
+        match self.peek()? {
+            b'"' => self.string(),
+            b'?' => {
+                self.pos += 1;
+                let value = match self.peek()? {
+                    b'1' => true,
+                    b'0' => false,
+                    _ => return None,
+                };
+                self.pos += 1;
+                Some(BareItem::Bool(value))
+            }
+            b'-' | b'0'..=b'9' => self.number(),
+            c if c.is_ascii_alphabetic() || c == b'*' => self.token(),
+            _ => None,
+        }
+    }
+
+    fn string(&mut self) -> Option<BareItem> {
+        self.pos += 1;
+        let mut out = String::new();
+        loop {
+            match self.peek()? {
+                b'\\' => {
+                    self.pos += 1;
+                    out.push(self.peek()? as char);
+                }
+                b'"' => {
+                    self.pos += 1;
+                    return Some(BareItem::Str(out));
+                }
+                c => out.push(c as char),
+            }
+            self.pos += 1;
+        }
+    }
+
+    fn token(&mut self) -> Option<BareItem> {
+        let start = self.pos;
+        while let Some(c) = self.peek() {
+            if c.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~:/".contains(&c) {
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+        Some(BareItem::Token(String::from_utf8_lossy(&self.input[start..self.pos]).into_owned()))
+    }
+
+    fn number(&mut self) -> Option<BareItem> {
+        let start = self.pos;
+        self.eat(b'-');
+        while matches!(self.peek(), Some(b'0'..=b'9') | Some(b'.')) {
+            self.pos += 1;
+        }
+        let text = std::str::from_utf8(&self.input[start..self.pos]).ok()?;
+        if text.contains('.') {
+            text.parse().ok().map(BareItem::Decimal)
+        } else {
+            text.parse().ok().map(BareItem::Int)
+        }
+    }
+}
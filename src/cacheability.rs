@@ -0,0 +1,93 @@
+use crate::cache_control::CacheDirectives;
+use crate::freshness::freshness_lifetime;
+use reqwest::header::HeaderMap;
+use serde::Serialize;
+
+/// Status codes a cache may store without explicit freshness information (RFC 9110 §15.1)
+pub const HEURISTICALLY_CACHEABLE: [u16; 12] = [200, 203, 204, 206, 300, 301, 308, 404, 405, 410, 414, 501];
+
+/// Whether one class of cache may store and reuse a response, and why
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Cacheability {
+    pub cacheable: bool,
+    pub reason: String,
+}
+
+impl Cacheability {
+    fn yes(reason: impl Into<String>) -> Self {
+        Cacheability { cacheable: true, reason: reason.into() }
+    }
+
+    fn no(reason: impl Into<String>) -> Self {
+        Cacheability { cacheable: false, reason: reason.into() }
+    }
+}
+
+/// Decides whether a private (browser) cache may reuse the response
+pub fn browser_cacheability(directives: &CacheDirectives, headers: &HeaderMap) -> Cacheability {
+    if directives.no_store {
+        return Cacheability::no("no-store forbids storing the response");
+    }
+    if directives.requires_revalidation() {
+        return Cacheability::no("no-cache requires revalidation on every use");
+    }
+
+    // s-maxage only applies to shared caches
+    let browser_directives = CacheDirectives { s_maxage: None, ..directives.clone() };
+    match freshness_lifetime(&browser_directives, headers) {
+        Some((0, source)) => Cacheability::no(format!("zero freshness lifetime ({})", source)),
+        Some((lifetime, source)) => Cacheability::yes(format!("fresh for {}s ({})", lifetime, source)),
+        None => Cacheability::no("no freshness information"),
+    }
+}
+
+/// Decides whether a shared cache (CDN or proxy) may reuse the response (RFC 9111 §3)
+pub fn shared_cacheability(directives: &CacheDirectives, headers: &HeaderMap, request_has_authorization: bool) -> Cacheability {
+    shared(directives, headers, request_has_authorization, None)
+}
+
+/// Like `shared_cacheability`, but a heuristically cacheable `status` (e.g. 301)
+/// may be stored without an explicit lifetime (RFC 9111 §4.2.2)
+pub fn shared_cacheability_for_status(
+    directives: &CacheDirectives,
+    headers: &HeaderMap,
+    request_has_authorization: bool,
+    status: u16,
+) -> Cacheability {
+    shared(directives, headers, request_has_authorization, Some(status))
+}
+
+fn shared(directives: &CacheDirectives, headers: &HeaderMap, request_has_authorization: bool, status: Option<u16>) -> Cacheability {
+    if directives.no_store {
+        return Cacheability::no("no-store forbids storing the response");
+    }
+    if directives.is_private() {
+        return Cacheability::no("private restricts the response to browser caches");
+    }
+    if request_has_authorization && !(directives.public || directives.s_maxage.is_some() || directives.must_revalidate) {
+        return Cacheability::no("request carried Authorization without public, s-maxage or must-revalidate");
+    }
+    if headers.contains_key("set-cookie") && !excludes_field(directives, "set-cookie") {
+        return Cacheability::no("response sets a cookie (Set-Cookie)");
+    }
+    if directives.requires_revalidation() {
+        return Cacheability::no("no-cache requires revalidation on every use");
+    }
+
+    match freshness_lifetime(directives, headers) {
+        Some((0, source)) => Cacheability::no(format!("zero freshness lifetime ({})", source)),
+        Some((lifetime, source)) => Cacheability::yes(format!("fresh for {}s ({})", lifetime, source)),
+        None if directives.public => Cacheability::yes("public without explicit lifetime"),
+        None => match status {
+            Some(status) if HEURISTICALLY_CACHEABLE.contains(&status) => {
+                Cacheability::yes(format!("{} is heuristically cacheable without explicit lifetime", status))
+            }
+            _ => Cacheability::no("no freshness information"),
+        },
+    }
+}
+
+/// True if a qualified `no-cache` or `private` names `field`, so the rest of the response stays reusable
+fn excludes_field(directives: &CacheDirectives, field: &str) -> bool {
+    directives.no_cache_fields.iter().any(|f| f == field) || directives.private_fields.iter().any(|f| f == field)
+}
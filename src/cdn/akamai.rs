@@ -0,0 +1,110 @@
+use super::{header, header_contains, pick, CacheTier, CdnProvider, TierRole};
+use crate::verdict::{CacheStatus, Verdict};
+use reqwest::header::HeaderMap;
+use std::collections::BTreeMap;
+
+/// `Pragma` value that makes Akamai return its cache debug headers
+pub const AKAMAI_DEBUG_PRAGMA: &str = "akamai-x-cache-on, akamai-x-cache-remote-on, akamai-x-check-cacheable, \
+     akamai-x-get-cache-key, akamai-x-get-true-cache-key, akamai-x-get-request-id";
+
+/// Akamai, identified by `AkamaiGHost`, Akamai request headers or debug pragma responses
+pub struct Akamai;
+
+impl CdnProvider for Akamai {
+    fn name(&self) -> &str {
+        "akamai"
+    }
+
+    fn detect(&self, headers: &HeaderMap) -> bool {
+        headers.contains_key("akamai-grn")
+            || headers.contains_key("x-akamai-request-id")
+            || headers.contains_key("x-akamai-transformed")
+            || headers.contains_key("x-check-cacheable")
+            || headers.contains_key("x-true-cache-key")
+            || header_contains(headers, "server", "akamai")
+            || header_contains(headers, "x-cache", "akamai")
+            || header_contains(headers, "x-cdn-provider", "akamai")
+    }
+
+    fn target_list(&self) -> &[&str] {
+        &["akamai-cache-control", "cdn-cache-control"]
+    }
+
+    fn extract(&self, headers: &HeaderMap) -> BTreeMap<String, String> {
+        pick(
+            headers,
+            &[
+                "x-cache",
+                "x-cache-remote",
+                "x-check-cacheable",
+                "x-cache-key",
+                "x-true-cache-key",
+                "akamai-grn",
+                "x-akamai-request-id",
+            ],
+        )
+    }
+
+    fn cache_status(&self, headers: &HeaderMap) -> Option<Verdict> {
+        let x_cache = header(headers, "x-cache")?;
+        let code = tcp_code(x_cache);
+        let not_cacheable = header(headers, "x-check-cacheable").is_some_and(|v| v.trim().eq_ignore_ascii_case("no"));
+
+        let verdict = match code.as_str() {
+            "TCP_HIT" => Verdict::new(CacheStatus::Hit, "Served from Akamai edge disk cache"),
+            "TCP_MEM_HIT" => Verdict::new(CacheStatus::Hit, "Served from Akamai edge memory cache"),
+            "TCP_IMS_HIT" => Verdict::new(CacheStatus::Hit, "Conditional request answered from Akamai cache"),
+            "TCP_NEGATIVE_HIT" => Verdict::new(CacheStatus::Hit, "Cached error response served by Akamai"),
+            "TCP_REFRESH_HIT" => Verdict::new(CacheStatus::Revalidated, "Stale object revalidated with origin (TCP_REFRESH_HIT)"),
+            "TCP_REFRESH_FAIL_HIT" => Verdict::new(CacheStatus::Stale, "Origin unreachable, stale object served"),
+            "TCP_REFRESH_MISS" => Verdict::new(CacheStatus::Miss, "Stale object replaced by a new copy from origin"),
+            "TCP_MISS" if not_cacheable => Verdict::new(CacheStatus::Uncacheable, "Not in Akamai cache and X-Check-Cacheable: NO"),
+            "TCP_MISS" => Verdict::new(CacheStatus::Miss, "Not in Akamai cache, fetched from origin"),
+            "TCP_DENIED" | "TCP_COOKIE_DENY" => Verdict::new(CacheStatus::Bypass, format!("Request denied by Akamai ({})", code)),
+            _ => return super::x_cache_verdict(x_cache, self.name()),
+        };
+        Some(verdict)
+    }
+
+    fn request_id(&self, headers: &HeaderMap) -> Option<String> {
+        header(headers, "x-akamai-request-id").map(|v| v.trim().to_string())
+    }
+
+    /// `X-Cache-Remote` reports the parent (midgress) tier when the debug pragma is sent
+    fn cache_tiers(&self, headers: &HeaderMap) -> Vec<CacheTier> {
+        let mut tiers = Vec::new();
+        if let Some(remote) = header(headers, "x-cache-remote") {
+            tiers.push(tier(TierRole::Shield, remote));
+        }
+        if !tiers.is_empty() {
+            if let Some(edge) = header(headers, "x-cache") {
+                tiers.push(tier(TierRole::Edge, edge));
+            }
+        }
+        tiers
+    }
+}
+
+/// First token of an Akamai `X-Cache` value, e.g. `TCP_HIT`
+fn tcp_code(x_cache: &str) -> String {
+    x_cache.split_whitespace().next().unwrap_or("").to_ascii_uppercase()
+}
+
+/// `X-Cache` values look like `TCP_HIT from a23-1-2-3.deploy.akamaitechnologies.com (AkamaiGHost/...)`
+fn tier(role: TierRole, x_cache: &str) -> CacheTier {
+    let code = tcp_code(x_cache);
+    let status = match code.as_str() {
+        "TCP_HIT" | "TCP_MEM_HIT" | "TCP_IMS_HIT" | "TCP_NEGATIVE_HIT" => CacheStatus::Hit,
+        "TCP_REFRESH_HIT" => CacheStatus::Revalidated,
+        "TCP_REFRESH_FAIL_HIT" => CacheStatus::Stale,
+        "TCP_MISS" | "TCP_REFRESH_MISS" => CacheStatus::Miss,
+        _ => CacheStatus::Unknown,
+    };
+    let node = x_cache
+        .split_whitespace()
+        .skip_while(|w| *w != "from")
+        .nth(1)
+        .map(str::to_string);
+
+    CacheTier { role, node, status, hits: None }
+}
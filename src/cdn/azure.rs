@@ -0,0 +1,35 @@
+use super::{header, pick, CdnProvider};
+use crate::verdict::{CacheStatus, Verdict};
+use reqwest::header::HeaderMap;
+use std::collections::BTreeMap;
+
+/// Azure Front Door, identified by `X-Azure-Ref`
+pub struct AzureFrontDoor;
+
+impl CdnProvider for AzureFrontDoor {
+    fn name(&self) -> &str {
+        "azure"
+    }
+
+    fn detect(&self, headers: &HeaderMap) -> bool {
+        headers.contains_key("x-azure-ref") || headers.contains_key("x-msedge-ref")
+    }
+
+    fn extract(&self, headers: &HeaderMap) -> BTreeMap<String, String> {
+        pick(headers, &["x-cache", "x-azure-ref", "x-msedge-ref"])
+    }
+
+    fn cache_status(&self, headers: &HeaderMap) -> Option<Verdict> {
+        let x_cache = header(headers, "x-cache")?;
+        let verdict = match x_cache.trim().to_ascii_uppercase().as_str() {
+            "TCP_HIT" => Verdict::new(CacheStatus::Hit, "Served from Azure Front Door edge cache"),
+            "TCP_REMOTE_HIT" => Verdict::new(CacheStatus::Hit, "Served from Azure Front Door regional cache"),
+            "TCP_MISS" => Verdict::new(CacheStatus::Miss, "Not in Azure Front Door cache"),
+            "CONFIG_NOCACHE" => Verdict::new(CacheStatus::Bypass, "Caching disabled by Front Door configuration"),
+            "PRIVATE_NOSTORE" => Verdict::new(CacheStatus::Uncacheable, "Origin marked the response private or no-store"),
+            "UNCACHEABLE" => Verdict::new(CacheStatus::Uncacheable, "Response is not cacheable"),
+            other => Verdict::new(CacheStatus::Unknown, format!("Unknown Azure Front Door status: {}", other)),
+        };
+        Some(verdict)
+    }
+}
@@ -0,0 +1,30 @@
+use super::{header, header_contains, pick, CdnProvider};
+use crate::verdict::{CacheStatus, Verdict};
+use reqwest::header::HeaderMap;
+use std::collections::BTreeMap;
+
+/// Bunny CDN, identified by `Server: BunnyCDN-*` or `CDN-PullZone`
+pub struct Bunny;
+
+impl CdnProvider for Bunny {
+    fn name(&self) -> &str {
+        "bunnycdn"
+    }
+
+    fn detect(&self, headers: &HeaderMap) -> bool {
+        header_contains(headers, "server", "bunnycdn") || headers.contains_key("cdn-pullzone")
+    }
+
+    fn extract(&self, headers: &HeaderMap) -> BTreeMap<String, String> {
+        pick(headers, &["cdn-cache", "cdn-pullzone", "cdn-requestid", "cdn-cachedat", "server"])
+    }
+
+    fn cache_status(&self, headers: &HeaderMap) -> Option<Verdict> {
+        let cdn_cache = header(headers, "cdn-cache")?;
+        let verdict = match CacheStatus::from_keyword(cdn_cache) {
+            Some(status) => Verdict::new(status, format!("Bunny CDN-Cache: {}", cdn_cache.trim())),
+            None => Verdict::new(CacheStatus::Unknown, format!("Unknown Bunny status: {}", cdn_cache)),
+        };
+        Some(verdict)
+    }
+}
@@ -0,0 +1,53 @@
+use super::{header, header_contains, pick, CdnProvider, EdgeLocation};
+use crate::verdict::{CacheStatus, Verdict};
+use reqwest::header::HeaderMap;
+use std::collections::BTreeMap;
+
+/// Cloudflare, identified by `CF-Ray` / `CF-Cache-Status`
+pub struct Cloudflare;
+
+impl CdnProvider for Cloudflare {
+    fn name(&self) -> &str {
+        "cloudflare"
+    }
+
+    fn detect(&self, headers: &HeaderMap) -> bool {
+        headers.contains_key("cf-ray")
+            || headers.contains_key("cf-cache-status")
+            || header_contains(headers, "server", "cloudflare")
+    }
+
+    fn target_list(&self) -> &[&str] {
+        &["cloudflare-cdn-cache-control", "cdn-cache-control"]
+    }
+
+    fn extract(&self, headers: &HeaderMap) -> BTreeMap<String, String> {
+        pick(headers, &["cf-cache-status", "cf-ray", "cf-apo-via", "cf-edge-cache"])
+    }
+
+    fn cache_status(&self, headers: &HeaderMap) -> Option<Verdict> {
+        let cf_status = header(headers, "cf-cache-status")?;
+        let verdict = match CacheStatus::from_keyword(cf_status) {
+            Some(CacheStatus::Hit) => Verdict::new(CacheStatus::Hit, "Served from Cloudflare cache"),
+            Some(CacheStatus::Miss) => Verdict::new(CacheStatus::Miss, "Origin fetch, not cached yet"),
+            Some(CacheStatus::Expired) => Verdict::new(CacheStatus::Expired, "Cache entry stale, revalidating"),
+            Some(CacheStatus::Stale) => Verdict::new(CacheStatus::Stale, "Serving stale content while revalidating"),
+            Some(CacheStatus::Bypass) => Verdict::new(CacheStatus::Bypass, "Cache rules bypassed"),
+            Some(CacheStatus::Revalidated) => Verdict::new(CacheStatus::Revalidated, "Cache validated with origin"),
+            Some(CacheStatus::Updating) => Verdict::new(CacheStatus::Updating, "Serving cached while updating"),
+            Some(CacheStatus::Dynamic) => Verdict::new(CacheStatus::Dynamic, "Content marked as uncacheable"),
+            _ => Verdict::new(CacheStatus::Unknown, format!("Unknown Cloudflare status: {}", cf_status)),
+        };
+        Some(verdict)
+    }
+
+    fn request_id(&self, headers: &HeaderMap) -> Option<String> {
+        header(headers, "cf-ray").map(|v| v.trim().to_string())
+    }
+
+    /// `CF-Ray` ends with the airport code of the data center, e.g. `8a1b2c3d4e5f6789-SJC`
+    fn edge_location(&self, headers: &HeaderMap) -> Option<EdgeLocation> {
+        let (_, pop) = header(headers, "cf-ray")?.rsplit_once('-')?;
+        Some(EdgeLocation::from_pop(pop))
+    }
+}
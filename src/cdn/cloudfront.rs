@@ -0,0 +1,52 @@
+use super::{header, header_contains, pick, CdnProvider, EdgeLocation};
+use crate::verdict::{CacheStatus, Verdict};
+use reqwest::header::HeaderMap;
+use std::collections::BTreeMap;
+
+/// Amazon CloudFront, identified by `X-Amz-Cf-*` headers
+pub struct CloudFront;
+
+impl CdnProvider for CloudFront {
+    fn name(&self) -> &str {
+        "cloudfront"
+    }
+
+    fn detect(&self, headers: &HeaderMap) -> bool {
+        headers.contains_key("x-amz-cf-id")
+            || headers.contains_key("x-amz-cf-pop")
+            || header_contains(headers, "x-cache", "cloudfront")
+            || header_contains(headers, "via", "cloudfront")
+            || header_contains(headers, "server", "cloudfront")
+    }
+
+    fn extract(&self, headers: &HeaderMap) -> BTreeMap<String, String> {
+        pick(headers, &["x-cache", "x-amz-cf-pop", "x-amz-cf-id"])
+    }
+
+    /// Decodes `X-Cache: <State> from cloudfront`
+    fn cache_status(&self, headers: &HeaderMap) -> Option<Verdict> {
+        let x_cache = header(headers, "x-cache")?;
+        let state = x_cache.split_whitespace().next().unwrap_or("");
+
+        let verdict = match state.to_ascii_lowercase().as_str() {
+            "hit" => Verdict::new(CacheStatus::Hit, "Served from CloudFront edge cache"),
+            "refreshhit" => Verdict::new(CacheStatus::Revalidated, "Stale copy revalidated with origin (RefreshHit)"),
+            "miss" => Verdict::new(CacheStatus::Miss, "Not in CloudFront cache, fetched from origin"),
+            "limitexceeded" => Verdict::new(CacheStatus::Bypass, "CloudFront limit exceeded, request not served from cache"),
+            "redirect" => Verdict::new(CacheStatus::Bypass, "Redirect generated by CloudFront (e.g. HTTP to HTTPS)"),
+            "functiongeneratedresponse" => Verdict::new(CacheStatus::Bypass, "Response generated by a CloudFront Function"),
+            "error" => Verdict::new(CacheStatus::Unknown, "Error from CloudFront (origin or edge returned an error)"),
+            _ => Verdict::new(CacheStatus::Unknown, format!("Unknown CloudFront status: {}", x_cache)),
+        };
+        Some(verdict)
+    }
+
+    fn request_id(&self, headers: &HeaderMap) -> Option<String> {
+        header(headers, "x-amz-cf-id").map(|v| v.trim().to_string())
+    }
+
+    /// POPs look like `IAD89-C1`: airport code, site number, then a tier suffix
+    fn edge_location(&self, headers: &HeaderMap) -> Option<EdgeLocation> {
+        header(headers, "x-amz-cf-pop").map(EdgeLocation::from_pop)
+    }
+}
@@ -0,0 +1,131 @@
+use super::{header, header_contains, pick, CacheTier, CdnProvider, EdgeLocation, TierRole};
+use crate::verdict::{CacheStatus, Verdict};
+use reqwest::header::HeaderMap;
+use std::collections::BTreeMap;
+
+/// Fastly, identified by `cache-*` node names in `X-Served-By`
+pub struct Fastly;
+
+impl CdnProvider for Fastly {
+    fn name(&self) -> &str {
+        "fastly"
+    }
+
+    fn detect(&self, headers: &HeaderMap) -> bool {
+        header(headers, "x-served-by").is_some_and(|v| v.trim_start().starts_with("cache-"))
+            || headers.contains_key("x-fastly-request-id")
+            || header_contains(headers, "server", "fastly")
+            || header_contains(headers, "x-cdn-provider", "fastly")
+    }
+
+    fn target_list(&self) -> &[&str] {
+        &["surrogate-control", "cdn-cache-control"]
+    }
+
+    fn extract(&self, headers: &HeaderMap) -> BTreeMap<String, String> {
+        pick(headers, &["x-cache", "x-cache-hits", "x-served-by", "x-timer", "x-fastly-request-id"])
+    }
+
+    /// The edge (last tier) decides the verdict; a shield hit behind an edge miss is called out
+    fn cache_status(&self, headers: &HeaderMap) -> Option<Verdict> {
+        let tiers = self.cache_tiers(headers);
+        let edge = tiers.last()?;
+        let edge_name = edge.node.as_deref().unwrap_or("edge");
+
+        if edge.status == CacheStatus::Miss {
+            if let Some(shield) = tiers[..tiers.len() - 1].iter().rev().find(|t| t.status.is_cached()) {
+                let shield_name = shield.node.as_deref().unwrap_or("shield");
+                return Some(Verdict::new(
+                    CacheStatus::Hit,
+                    format!("Shield HIT, edge MISS - served from shield {} via {}", shield_name, edge_name),
+                ));
+            }
+            let reason = if tiers.len() > 1 { "MISS at every Fastly tier".to_string() } else { format!("Edge MISS at {}", edge_name) };
+            return Some(Verdict::new(CacheStatus::Miss, reason));
+        }
+
+        let reason = match edge.status {
+            CacheStatus::Hit => format!("Edge HIT at {}", edge_name),
+            CacheStatus::Stale => format!("Edge served stale content at {}", edge_name),
+            CacheStatus::Bypass => format!("Edge PASS at {}, cache bypassed", edge_name),
+            _ => format!("Unknown Fastly status at {}", edge_name),
+        };
+        Some(Verdict::new(edge.status, reason))
+    }
+
+    fn request_id(&self, headers: &HeaderMap) -> Option<String> {
+        header(headers, "x-fastly-request-id").map(|v| v.trim().to_string())
+    }
+
+    fn edge_location(&self, headers: &HeaderMap) -> Option<EdgeLocation> {
+        let edge = header(headers, "x-served-by")?.split(',').next_back()?.trim().to_string();
+        node_airport(&edge).map(|airport| EdgeLocation { pop: edge, ..EdgeLocation::from_pop(&airport) })
+    }
+
+    /// Fastly appends one entry per tier, so the first value is the shield
+    /// closest to origin and the last value is the edge closest to the client
+    fn cache_tiers(&self, headers: &HeaderMap) -> Vec<CacheTier> {
+        let statuses = split_values(header(headers, "x-cache"));
+        if statuses.is_empty() {
+            return Vec::new();
+        }
+        let nodes = split_values(header(headers, "x-served-by"));
+        let hits = split_values(header(headers, "x-cache-hits"));
+        let count = statuses.len();
+
+        statuses
+            .iter()
+            .enumerate()
+            .map(|(i, status)| {
+                let from_end = count - i;
+                let role = if from_end == 1 {
+                    TierRole::Edge
+                } else if i == 0 {
+                    TierRole::Shield
+                } else {
+                    TierRole::Intermediate
+                };
+                CacheTier {
+                    role,
+                    node: aligned(&nodes, from_end).map(str::to_string),
+                    status: tier_status(status),
+                    hits: aligned(&hits, from_end).and_then(|h| h.parse().ok()),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Picks the value `from_end` positions from the end, so lists of differing
+/// length still line up on the edge
+fn aligned<'a>(values: &[&'a str], from_end: usize) -> Option<&'a str> {
+    values.len().checked_sub(from_end).map(|i| values[i])
+}
+
+fn split_values(value: Option<&str>) -> Vec<&str> {
+    value
+        .map(|v| v.split(',').map(str::trim).filter(|s| !s.is_empty()).collect())
+        .unwrap_or_default()
+}
+
+fn tier_status(value: &str) -> CacheStatus {
+    match value.to_ascii_uppercase().as_str() {
+        "HIT" | "HIT-SYNTH" | "HIT-WAIT" => CacheStatus::Hit,
+        "HIT-STALE" => CacheStatus::Stale,
+        "MISS" => CacheStatus::Miss,
+        "PASS" => CacheStatus::Bypass,
+        _ => CacheStatus::Unknown,
+    }
+}
+
+/// Extracts the airport code from node names like `cache-lax-kwhp1940065-LAX` or `cache-iad-1`
+fn node_airport(node: &str) -> Option<String> {
+    let rest = node.strip_prefix("cache-")?;
+    if let Some((_, suffix)) = rest.rsplit_once('-') {
+        if suffix.len() == 3 && suffix.chars().all(|c| c.is_ascii_alphabetic()) {
+            return Some(suffix.to_ascii_uppercase());
+        }
+    }
+    let code: String = rest.chars().take_while(|c| c.is_ascii_alphabetic()).collect();
+    (code.len() == 3).then(|| code.to_ascii_uppercase())
+}
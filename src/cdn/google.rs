@@ -0,0 +1,29 @@
+use super::{header_contains, pick, CdnProvider};
+use crate::verdict::{CacheStatus, Verdict};
+use reqwest::header::HeaderMap;
+use std::collections::BTreeMap;
+
+/// Google Cloud CDN, identified by `Via: 1.1 google`. `Server: Google Frontend`
+/// alone is App Engine or another Google front end, not Cloud CDN.
+pub struct GoogleCloudCdn;
+
+impl CdnProvider for GoogleCloudCdn {
+    fn name(&self) -> &str {
+        "google"
+    }
+
+    fn detect(&self, headers: &HeaderMap) -> bool {
+        header_contains(headers, "via", "google")
+    }
+
+    fn extract(&self, headers: &HeaderMap) -> BTreeMap<String, String> {
+        pick(headers, &["via", "age", "x-goog-hash"])
+    }
+
+    /// Cloud CDN has no status header; a non-zero `Age` behind its `Via` is
+    /// the only sign of a cache hit
+    fn cache_status(&self, headers: &HeaderMap) -> Option<Verdict> {
+        let age = headers.get("age")?.to_str().ok()?.trim().parse::<u64>().ok()?;
+        (age > 0).then(|| Verdict::new(CacheStatus::Hit, format!("Served from Google Cloud CDN cache (Age {})", age)))
+    }
+}
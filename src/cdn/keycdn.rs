@@ -0,0 +1,25 @@
+use super::{header, header_contains, pick, x_cache_verdict, CdnProvider};
+use crate::verdict::Verdict;
+use reqwest::header::HeaderMap;
+use std::collections::BTreeMap;
+
+/// KeyCDN, identified by `Server: keycdn-engine`
+pub struct KeyCdn;
+
+impl CdnProvider for KeyCdn {
+    fn name(&self) -> &str {
+        "keycdn"
+    }
+
+    fn detect(&self, headers: &HeaderMap) -> bool {
+        header_contains(headers, "server", "keycdn")
+    }
+
+    fn extract(&self, headers: &HeaderMap) -> BTreeMap<String, String> {
+        pick(headers, &["x-cache", "x-edge-location", "x-shield"])
+    }
+
+    fn cache_status(&self, headers: &HeaderMap) -> Option<Verdict> {
+        x_cache_verdict(header(headers, "x-cache")?, self.name())
+    }
+}
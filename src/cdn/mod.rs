@@ -0,0 +1,191 @@
+//! CDN provider detection and per-provider header decoding.
+//!
+//! Each provider knows how to recognise itself from response headers, which
+//! of its headers are worth reporting, and how to map its vendor-specific
+//! cache status onto a normalized [`CacheStatus`](crate::verdict::CacheStatus).
+//! In-house edges can be supported by implementing [`CdnProvider`] and
+//! registering it on a [`CdnRegistry`].
+
+mod akamai;
+mod azure;
+mod bunny;
+mod cloudflare;
+mod cloudfront;
+mod fastly;
+mod google;
+mod keycdn;
+mod nginx;
+mod pop;
+mod squid;
+mod varnish;
+
+pub use akamai::{Akamai, AKAMAI_DEBUG_PRAGMA};
+pub use azure::AzureFrontDoor;
+pub use bunny::Bunny;
+pub use cloudflare::Cloudflare;
+pub use cloudfront::CloudFront;
+pub use fastly::Fastly;
+pub use google::GoogleCloudCdn;
+pub use keycdn::KeyCdn;
+pub use nginx::Nginx;
+pub use pop::{airport_city, EdgeLocation};
+pub use squid::Squid;
+pub use varnish::Varnish;
+
+use crate::cache_control::DEFAULT_TARGET_LIST;
+use crate::verdict::{CacheStatus, Verdict};
+use reqwest::header::HeaderMap;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Arc;
+
+/// Position of a cache layer in a multi-tier CDN
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TierRole {
+    /// Layer closest to the origin
+    Shield,
+    Intermediate,
+    /// Layer closest to the client
+    Edge,
+}
+
+impl fmt::Display for TierRole {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            TierRole::Shield => "shield",
+            TierRole::Intermediate => "intermediate",
+            TierRole::Edge => "edge",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Cache result of one layer in a multi-tier CDN
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CacheTier {
+    pub role: TierRole,
+    pub node: Option<String>,
+    pub status: CacheStatus,
+    pub hits: Option<u64>,
+}
+
+/// A CDN or caching proxy that can be recognised from response headers
+pub trait CdnProvider: Send + Sync {
+    /// Identifier reported as `cdn_provider`
+    fn name(&self) -> &str;
+
+    /// True if the response was served through this provider
+    fn detect(&self, headers: &HeaderMap) -> bool;
+
+    /// Provider-specific header values worth reporting
+    fn extract(&self, headers: &HeaderMap) -> BTreeMap<String, String>;
+
+    /// Normalized cache status, or `None` if the headers do not say
+    fn cache_status(&self, headers: &HeaderMap) -> Option<Verdict>;
+
+    /// Provider request identifier, useful for support tickets
+    fn request_id(&self, _headers: &HeaderMap) -> Option<String> {
+        None
+    }
+
+    /// Edge location (POP) that served the response
+    fn edge_location(&self, _headers: &HeaderMap) -> Option<EdgeLocation> {
+        None
+    }
+
+    /// Targeted Cache-Control fields this provider obeys, most specific first
+    fn target_list(&self) -> &[&str] {
+        &DEFAULT_TARGET_LIST
+    }
+
+    /// Per-layer results, ordered from origin side to client side
+    fn cache_tiers(&self, _headers: &HeaderMap) -> Vec<CacheTier> {
+        Vec::new()
+    }
+}
+
+/// Ordered set of providers consulted during detection
+#[derive(Clone)]
+pub struct CdnRegistry {
+    providers: Vec<Arc<dyn CdnProvider>>,
+}
+
+impl CdnRegistry {
+    /// Creates a registry with no providers
+    pub fn empty() -> Self {
+        CdnRegistry { providers: Vec::new() }
+    }
+
+    /// Registers a provider ahead of all existing ones, so custom providers
+    /// win over built-ins that would otherwise match the same headers
+    pub fn register(&mut self, provider: impl CdnProvider + 'static) -> &mut Self {
+        self.providers.insert(0, Arc::new(provider));
+        self
+    }
+
+    /// Returns the first provider that recognises the headers
+    pub fn detect(&self, headers: &HeaderMap) -> Option<&dyn CdnProvider> {
+        self.providers
+            .iter()
+            .find(|p| p.detect(headers))
+            .map(|p| p.as_ref())
+    }
+
+    /// Names of the registered providers in detection order
+    pub fn names(&self) -> Vec<&str> {
+        self.providers.iter().map(|p| p.name()).collect()
+    }
+}
+
+impl Default for CdnRegistry {
+    /// Registry with every built-in provider. Order matters: Fastly also
+    /// sends `Via: 1.1 varnish`, so it must be checked before Varnish.
+    fn default() -> Self {
+        let builtins: Vec<Arc<dyn CdnProvider>> = vec![
+            Arc::new(Cloudflare),
+            Arc::new(Fastly),
+            Arc::new(CloudFront),
+            Arc::new(Akamai),
+            Arc::new(AzureFrontDoor),
+            Arc::new(GoogleCloudCdn),
+            Arc::new(Bunny),
+            Arc::new(KeyCdn),
+            Arc::new(Squid),
+            Arc::new(Nginx),
+            Arc::new(Varnish),
+        ];
+        CdnRegistry { providers: builtins }
+    }
+}
+
+/// Returns a header value as a string, ignoring values that are not valid UTF-8
+pub fn header<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers.get(name).and_then(|v| v.to_str().ok())
+}
+
+/// True if the header is present and contains `needle` (case-insensitive)
+pub fn header_contains(headers: &HeaderMap, name: &str, needle: &str) -> bool {
+    header(headers, name).is_some_and(|v| v.to_ascii_lowercase().contains(needle))
+}
+
+/// Collects the listed headers that are present into a name → value map
+pub fn pick(headers: &HeaderMap, names: &[&str]) -> BTreeMap<String, String> {
+    names
+        .iter()
+        .filter_map(|name| header(headers, name).map(|v| (name.to_string(), v.to_string())))
+        .collect()
+}
+
+/// Generic `X-Cache` interpretation: any "hit" is a HIT, any "miss" is a MISS
+pub fn x_cache_verdict(x_cache: &str, provider: &str) -> Option<Verdict> {
+    let cache_lower = x_cache.to_lowercase();
+    if cache_lower.contains("hit") {
+        Some(Verdict::new(CacheStatus::Hit, format!("Served from {} cache", provider)))
+    } else if cache_lower.contains("miss") {
+        Some(Verdict::new(CacheStatus::Miss, format!("Not in {} cache", provider)))
+    } else {
+        None
+    }
+}
@@ -0,0 +1,40 @@
+use super::{header, pick, CdnProvider};
+use crate::verdict::{CacheStatus, Verdict};
+use reqwest::header::HeaderMap;
+use std::collections::BTreeMap;
+
+/// Status headers commonly set to `$upstream_cache_status`
+const STATUS_HEADERS: [&str; 2] = ["x-cache-status", "x-proxy-cache"];
+
+/// Nginx `proxy_cache`, identified by `X-Cache-Status` (or `X-Proxy-Cache`)
+pub struct Nginx;
+
+impl CdnProvider for Nginx {
+    fn name(&self) -> &str {
+        "nginx"
+    }
+
+    fn detect(&self, headers: &HeaderMap) -> bool {
+        STATUS_HEADERS.iter().any(|name| headers.contains_key(*name))
+    }
+
+    fn extract(&self, headers: &HeaderMap) -> BTreeMap<String, String> {
+        pick(headers, &["x-cache-status", "x-proxy-cache", "server"])
+    }
+
+    /// Maps `$upstream_cache_status` values
+    fn cache_status(&self, headers: &HeaderMap) -> Option<Verdict> {
+        let status = STATUS_HEADERS.iter().find_map(|name| header(headers, name))?;
+        let verdict = match CacheStatus::from_keyword(status) {
+            Some(CacheStatus::Hit) => Verdict::new(CacheStatus::Hit, "Served from Nginx proxy_cache"),
+            Some(CacheStatus::Miss) => Verdict::new(CacheStatus::Miss, "Not in Nginx proxy_cache, fetched from upstream"),
+            Some(CacheStatus::Expired) => Verdict::new(CacheStatus::Expired, "Cached entry expired, fetched from upstream"),
+            Some(CacheStatus::Stale) => Verdict::new(CacheStatus::Stale, "Stale entry served (proxy_cache_use_stale)"),
+            Some(CacheStatus::Updating) => Verdict::new(CacheStatus::Updating, "Stale entry served while the cache is updated"),
+            Some(CacheStatus::Revalidated) => Verdict::new(CacheStatus::Revalidated, "Entry revalidated with upstream (proxy_cache_revalidate)"),
+            Some(CacheStatus::Bypass) => Verdict::new(CacheStatus::Bypass, "Cache bypassed (proxy_cache_bypass)"),
+            _ => Verdict::new(CacheStatus::Unknown, format!("Unknown Nginx cache status: {}", status)),
+        };
+        Some(verdict)
+    }
+}
@@ -0,0 +1,83 @@
+use serde::Serialize;
+
+/// Point of presence that served a response
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EdgeLocation {
+    /// Raw POP identifier as sent by the provider, e.g. `IAD89-C1`
+    pub pop: String,
+    /// IATA airport code the provider names the POP after
+    pub airport: Option<String>,
+    /// City for well-known airport codes
+    pub city: Option<String>,
+}
+
+impl EdgeLocation {
+    /// Builds a location from a POP identifier whose first three letters are an airport code
+    pub fn from_pop(pop: &str) -> Self {
+        let pop = pop.trim();
+        let airport: String = pop.chars().take_while(|c| c.is_ascii_alphabetic()).collect();
+        let airport = (airport.len() == 3).then(|| airport.to_ascii_uppercase());
+        let city = airport.as_deref().and_then(airport_city).map(str::to_string);
+
+        EdgeLocation { pop: pop.to_string(), airport, city }
+    }
+}
+
+/// City served by a well-known CDN POP airport code
+pub fn airport_city(code: &str) -> Option<&'static str> {
+    let city = match code {
+        "AMS" => "Amsterdam",
+        "ARN" => "Stockholm",
+        "ATL" => "Atlanta",
+        "BKK" => "Bangkok",
+        "BOM" => "Mumbai",
+        "BOS" => "Boston",
+        "BRU" => "Brussels",
+        "CDG" => "Paris",
+        "CPT" => "Cape Town",
+        "DEL" => "New Delhi",
+        "DEN" => "Denver",
+        "DFW" => "Dallas",
+        "DUB" => "Dublin",
+        "DXB" => "Dubai",
+        "EWR" => "Newark",
+        "FRA" => "Frankfurt",
+        "GRU" => "São Paulo",
+        "HEL" => "Helsinki",
+        "HKG" => "Hong Kong",
+        "IAD" => "Washington, D.C.",
+        "ICN" => "Seoul",
+        "JFK" => "New York",
+        "JNB" => "Johannesburg",
+        "KIX" => "Osaka",
+        "LAX" => "Los Angeles",
+        "LHR" => "London",
+        "MAD" => "Madrid",
+        "MAN" => "Manchester",
+        "MEL" => "Melbourne",
+        "MIA" => "Miami",
+        "MRS" => "Marseille",
+        "MUC" => "Munich",
+        "MXP" => "Milan",
+        "NRT" => "Tokyo",
+        "ORD" => "Chicago",
+        "OSL" => "Oslo",
+        "PHX" => "Phoenix",
+        "PRG" => "Prague",
+        "SEA" => "Seattle",
+        "SFO" => "San Francisco",
+        "SIN" => "Singapore",
+        "SJC" => "San Jose",
+        "SYD" => "Sydney",
+        "TLV" => "Tel Aviv",
+        "TPE" => "Taipei",
+        "VIE" => "Vienna",
+        "WAW" => "Warsaw",
+        "YUL" => "Montreal",
+        "YVR" => "Vancouver",
+        "YYZ" => "Toronto",
+        "ZRH" => "Zurich",
+        _ => return None,
+    };
+    Some(city)
+}
@@ -0,0 +1,46 @@
+use super::{header, header_contains, pick, CdnProvider};
+use crate::verdict::{CacheStatus, Verdict};
+use reqwest::header::HeaderMap;
+use std::collections::BTreeMap;
+
+/// Squid, identified by `X-Cache-Lookup` or a `squid` Via/Server token
+pub struct Squid;
+
+impl CdnProvider for Squid {
+    fn name(&self) -> &str {
+        "squid"
+    }
+
+    fn detect(&self, headers: &HeaderMap) -> bool {
+        headers.contains_key("x-cache-lookup")
+            || header_contains(headers, "via", "squid")
+            || header_contains(headers, "server", "squid")
+    }
+
+    fn extract(&self, headers: &HeaderMap) -> BTreeMap<String, String> {
+        pick(headers, &["x-cache", "x-cache-lookup", "via"])
+    }
+
+    /// `X-Cache` says whether the response was served from cache,
+    /// `X-Cache-Lookup` whether a copy existed when the request arrived
+    fn cache_status(&self, headers: &HeaderMap) -> Option<Verdict> {
+        let served = header(headers, "x-cache").map(first_word);
+        let lookup = header(headers, "x-cache-lookup").map(first_word);
+
+        let verdict = match (served.as_deref(), lookup.as_deref()) {
+            (Some("HIT"), _) => Verdict::new(CacheStatus::Hit, "Served from Squid cache"),
+            (Some("MISS"), Some("HIT")) => Verdict::new(CacheStatus::Expired, "Copy in Squid cache was not usable, fetched from origin"),
+            (Some("MISS"), _) => Verdict::new(CacheStatus::Miss, "Not in Squid cache"),
+            (_, Some("HIT")) => Verdict::new(CacheStatus::Hit, "Squid cache lookup found a copy"),
+            (_, Some("MISS")) => Verdict::new(CacheStatus::Miss, "Squid cache lookup found no copy"),
+            (None, None) => return None,
+            _ => Verdict::new(CacheStatus::Unknown, "Squid did not look up the cache"),
+        };
+        Some(verdict)
+    }
+}
+
+/// Squid values look like `HIT from proxy.example.com:3128`
+fn first_word(value: &str) -> String {
+    value.split_whitespace().next().unwrap_or("").to_ascii_uppercase()
+}
@@ -0,0 +1,48 @@
+use super::{header, header_contains, pick, x_cache_verdict, CdnProvider};
+use crate::verdict::{CacheStatus, Verdict};
+use reqwest::header::HeaderMap;
+use std::collections::BTreeMap;
+
+/// Self-hosted Varnish, identified by `X-Varnish` or `Via: 1.1 varnish`
+pub struct Varnish;
+
+impl CdnProvider for Varnish {
+    fn name(&self) -> &str {
+        "varnish"
+    }
+
+    fn detect(&self, headers: &HeaderMap) -> bool {
+        headers.contains_key("x-varnish") || header_contains(headers, "via", "varnish")
+    }
+
+    fn target_list(&self) -> &[&str] {
+        &["surrogate-control"]
+    }
+
+    fn extract(&self, headers: &HeaderMap) -> BTreeMap<String, String> {
+        pick(headers, &["x-varnish", "via", "x-cache", "x-cache-hits", "age"])
+    }
+
+    /// `X-Varnish` carries the current transaction ID, followed by the ID of
+    /// the transaction that stored the object when the response is a hit
+    fn cache_status(&self, headers: &HeaderMap) -> Option<Verdict> {
+        if let Some(ids) = header(headers, "x-varnish") {
+            let ids: Vec<&str> = ids.split_whitespace().collect();
+            return match ids.as_slice() {
+                [_, stored_by] => Some(Verdict::new(
+                    CacheStatus::Hit,
+                    format!("Served from Varnish cache (object stored by transaction {})", stored_by),
+                )),
+                [_] => Some(Verdict::new(CacheStatus::Miss, "Varnish fetched from backend (single X-Varnish ID)")),
+                _ => None,
+            };
+        }
+
+        // Without X-Varnish only a VCL-provided X-Cache can tell
+        x_cache_verdict(header(headers, "x-cache")?, self.name())
+    }
+
+    fn request_id(&self, headers: &HeaderMap) -> Option<String> {
+        header(headers, "x-varnish")?.split_whitespace().next().map(str::to_string)
+    }
+}
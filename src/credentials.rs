@@ -0,0 +1,102 @@
+use crate::verdict::CacheStatus;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, AUTHORIZATION, COOKIE};
+use serde::Serialize;
+
+/// Request headers that identify a user and commonly change caching
+pub const CREDENTIAL_HEADERS: [HeaderName; 2] = [AUTHORIZATION, COOKIE];
+
+/// Verdict with the user's credentials compared with an anonymous request
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CredentialImpact {
+    /// Credential headers that were sent, lowercased
+    pub sent: Vec<String>,
+    pub with_credentials: CacheStatus,
+    pub without_credentials: CacheStatus,
+    pub anonymous_verdict: String,
+    pub changed: bool,
+}
+
+impl CredentialImpact {
+    pub fn new(sent: Vec<String>, with_credentials: CacheStatus, without_credentials: CacheStatus, anonymous_verdict: String) -> Self {
+        CredentialImpact {
+            sent,
+            with_credentials,
+            without_credentials,
+            anonymous_verdict,
+            changed: with_credentials != without_credentials,
+        }
+    }
+}
+
+/// Parses a `Name: value` header line as given to `--header`
+pub fn parse_header_line(line: &str) -> Result<(HeaderName, HeaderValue), String> {
+    let (name, value) = line.split_once(':').ok_or_else(|| format!("expected 'Name: value', got '{}'", line))?;
+    let name = HeaderName::from_bytes(name.trim().as_bytes()).map_err(|e| format!("invalid header name '{}': {}", name.trim(), e))?;
+    let value = HeaderValue::from_str(value.trim()).map_err(|e| format!("invalid value for {}: {}", name, e))?;
+    Ok((name, value))
+}
+
+/// `Authorization` value for `--bearer`
+pub fn bearer(token: &str) -> Result<HeaderValue, String> {
+    let mut value = HeaderValue::from_str(&format!("Bearer {}", token.trim())).map_err(|e| format!("invalid bearer token: {}", e))?;
+    value.set_sensitive(true);
+    Ok(value)
+}
+
+/// `Authorization` value for `--basic-auth user:password`
+pub fn basic_auth(user_password: &str) -> Result<HeaderValue, String> {
+    if !user_password.contains(':') {
+        return Err("expected 'user:password'".to_string());
+    }
+    let mut value = HeaderValue::from_str(&format!("Basic {}", STANDARD.encode(user_password)))
+        .map_err(|e| format!("invalid basic auth: {}", e))?;
+    value.set_sensitive(true);
+    Ok(value)
+}
+
+/// Request headers from `--header` lines, `--cookie` values and the
+/// `Authorization` of `--bearer` or `--basic-auth`. Cookies are appended to a
+/// `Cookie` header given with `--header`; a second `Authorization` is an error.
+pub fn request_headers(
+    lines: &[(HeaderName, HeaderValue)],
+    cookies: &[String],
+    authorization: Option<&HeaderValue>,
+) -> Result<HeaderMap, String> {
+    let mut headers = HeaderMap::new();
+    for (name, value) in lines {
+        headers.append(name, value.clone());
+    }
+    if !cookies.is_empty() {
+        let mut pairs: Vec<&str> = headers.get_all(COOKIE).iter().filter_map(|v| v.to_str().ok()).collect();
+        pairs.extend(cookies.iter().map(String::as_str));
+        let value = HeaderValue::from_str(&pairs.join("; ")).map_err(|e| format!("invalid cookie: {}", e))?;
+        headers.insert(COOKIE, value);
+    }
+    if let Some(value) = authorization {
+        if headers.contains_key(AUTHORIZATION) {
+            return Err("--bearer/--basic-auth cannot be combined with an Authorization --header".to_string());
+        }
+        headers.insert(AUTHORIZATION, value.clone());
+    }
+    Ok(headers)
+}
+
+/// Credential headers present in `headers`, lowercased
+pub fn sent_credentials(headers: &HeaderMap) -> Vec<String> {
+    CREDENTIAL_HEADERS
+        .iter()
+        .filter(|name| headers.contains_key(*name))
+        .map(|name| name.to_string())
+        .collect()
+}
+
+/// `headers` without any credential header
+pub fn anonymous(headers: &HeaderMap) -> HeaderMap {
+    let mut headers = headers.clone();
+    for name in &CREDENTIAL_HEADERS {
+        headers.remove(name);
+    }
+    headers
+}
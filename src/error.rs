@@ -0,0 +1,146 @@
+use serde::Serialize;
+use std::error::Error;
+use std::fmt;
+
+/// What went wrong while scanning a URL
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    Dns,
+    Connect,
+    Tls,
+    Timeout,
+    HttpStatus,
+    BodyRead,
+    InvalidHeader,
+    InvalidUrl,
+    Other,
+}
+
+impl ErrorKind {
+    /// Network-level failures that may succeed on another attempt
+    pub fn is_transient(&self) -> bool {
+        matches!(self, ErrorKind::Dns | ErrorKind::Connect | ErrorKind::Timeout | ErrorKind::BodyRead)
+    }
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ErrorKind::Dns => "DNS error",
+            ErrorKind::Connect => "connection error",
+            ErrorKind::Tls => "TLS error",
+            ErrorKind::Timeout => "timeout",
+            ErrorKind::HttpStatus => "HTTP status error",
+            ErrorKind::BodyRead => "body read error",
+            ErrorKind::InvalidHeader => "invalid header",
+            ErrorKind::InvalidUrl => "invalid URL",
+            ErrorKind::Other => "error",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A classified scan failure, serialized into the JSON results
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ScanError {
+    pub kind: ErrorKind,
+    pub message: String,
+    /// Status code for `HttpStatus` errors
+    pub status: Option<u16>,
+    /// Requests made before giving up
+    pub attempts: u32,
+}
+
+impl ScanError {
+    pub fn new(kind: ErrorKind, message: impl Into<String>) -> Self {
+        ScanError { kind, message: message.into(), status: None, attempts: 1 }
+    }
+
+    pub fn http_status(status: u16) -> Self {
+        ScanError { status: Some(status), ..ScanError::new(ErrorKind::HttpStatus, format!("server answered {}", status)) }
+    }
+
+    pub fn with_attempts(self, attempts: u32) -> Self {
+        ScanError { attempts, ..self }
+    }
+
+    /// Classifies a reqwest failure by walking its source chain
+    pub fn from_reqwest(error: &reqwest::Error) -> Self {
+        let message = error_chain(error);
+        let lower = message.to_ascii_lowercase();
+
+        let kind = if error.is_timeout() {
+            ErrorKind::Timeout
+        } else if error.is_builder() {
+            if lower.contains("url") { ErrorKind::InvalidUrl } else { ErrorKind::InvalidHeader }
+        } else if error.is_body() || error.is_decode() {
+            ErrorKind::BodyRead
+        } else if error.is_status() {
+            ErrorKind::HttpStatus
+        } else if lower.contains("dns error") || lower.contains("failed to lookup address") || lower.contains("name or service not known") {
+            ErrorKind::Dns
+        } else if lower.contains("certificate") || lower.contains("tls") || lower.contains("ssl") || lower.contains("handshake") {
+            ErrorKind::Tls
+        } else if error.is_connect() || error.is_request() {
+            ErrorKind::Connect
+        } else {
+            ErrorKind::Other
+        };
+
+        ScanError { status: error.status().map(|s| s.as_u16()), ..ScanError::new(kind, message) }
+    }
+}
+
+impl fmt::Display for ScanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.kind, self.message)?;
+        if self.attempts > 1 {
+            write!(f, " (after {} attempts)", self.attempts)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for ScanError {}
+
+impl From<reqwest::Error> for ScanError {
+    fn from(error: reqwest::Error) -> Self {
+        ScanError::from_reqwest(&error)
+    }
+}
+
+impl From<reqwest::header::InvalidHeaderValue> for ScanError {
+    fn from(error: reqwest::header::InvalidHeaderValue) -> Self {
+        ScanError::new(ErrorKind::InvalidHeader, error.to_string())
+    }
+}
+
+/// Keeps a `ScanError` that was boxed on the way up; anything else becomes `Other`
+impl From<Box<dyn Error + Send + Sync>> for ScanError {
+    fn from(error: Box<dyn Error + Send + Sync>) -> Self {
+        match error.downcast::<ScanError>() {
+            Ok(scan_error) => *scan_error,
+            Err(error) => match error.downcast::<reqwest::Error>() {
+                Ok(reqwest_error) => ScanError::from_reqwest(&reqwest_error),
+                Err(error) => ScanError::new(ErrorKind::Other, error.to_string()),
+            },
+        }
+    }
+}
+
+/// Joins an error and its sources, since reqwest's own message hides the
+/// cause; hyper repeats causes in its messages, so those are not added twice
+fn error_chain(error: &dyn Error) -> String {
+    let mut message = error.to_string();
+    let mut source = error.source();
+    while let Some(cause) = source {
+        let cause_message = cause.to_string();
+        if !message.contains(&cause_message) {
+            message.push_str(": ");
+            message.push_str(&cause_message);
+        }
+        source = cause.source();
+    }
+    message
+}
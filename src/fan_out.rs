@@ -0,0 +1,135 @@
+use crate::cache_checker::observed_verdict;
+use crate::cdn::CdnRegistry;
+use crate::http_client::{RequestMethod, SniperClient};
+use crate::verdict::CacheStatus;
+use reqwest::header::{HeaderMap, AUTHORIZATION};
+use reqwest::Url;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::net::IpAddr;
+use tokio::net::lookup_host;
+use tokio::task::JoinSet;
+
+/// Which addresses a fan-out scan covers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FanOutTargets {
+    /// Every A/AAAA record of the URL's host
+    AllRecords,
+    Addresses(Vec<IpAddr>),
+}
+
+/// Result of scanning the URL through one address
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IpScan {
+    pub ip: IpAddr,
+    pub http_status: Option<u16>,
+    pub cache_status: Option<CacheStatus>,
+    pub age: Option<String>,
+    /// POP or edge node that answered, when the provider reveals it
+    pub pop: Option<String>,
+    pub error: Option<String>,
+}
+
+impl IpScan {
+    pub fn from_response(
+        ip: IpAddr,
+        http_status: u16,
+        headers: &HeaderMap,
+        registry: &CdnRegistry,
+        request_has_authorization: bool,
+    ) -> Self {
+        let pop = registry.detect(headers).and_then(|p| p.edge_location(headers)).map(|l| l.pop);
+        let cache_status = observed_verdict(headers, registry, request_has_authorization).map_or(CacheStatus::Unknown, |v| v.status);
+        IpScan {
+            ip,
+            http_status: Some(http_status),
+            cache_status: Some(cache_status),
+            age: headers.get("age").and_then(|v| v.to_str().ok()).map(str::to_string),
+            pop,
+            error: None,
+        }
+    }
+
+    pub fn failed(ip: IpAddr, error: impl Into<String>) -> Self {
+        IpScan { ip, http_status: None, cache_status: None, age: None, pop: None, error: Some(error.into()) }
+    }
+}
+
+/// Per-address results plus a consistency summary
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FanOutReport {
+    pub scans: Vec<IpScan>,
+    /// Number of addresses per cache status; failed scans count as `ERROR`
+    pub status_counts: BTreeMap<String, usize>,
+    /// True when every address answered with the same cache status
+    pub consistent: bool,
+}
+
+impl FanOutReport {
+    pub fn summarize(scans: Vec<IpScan>) -> Self {
+        let mut status_counts = BTreeMap::new();
+        for scan in &scans {
+            let key = scan.cache_status.map(|s| s.to_string()).unwrap_or_else(|| "ERROR".to_string());
+            *status_counts.entry(key).or_insert(0) += 1;
+        }
+        let consistent = status_counts.len() == 1 && !status_counts.contains_key("ERROR");
+        FanOutReport { scans, status_counts, consistent }
+    }
+}
+
+/// Addresses for `url` according to `targets`
+pub async fn target_addresses(url: &str, targets: &FanOutTargets) -> Result<Vec<IpAddr>, Box<dyn Error + Send + Sync>> {
+    let addrs = match targets {
+        FanOutTargets::Addresses(addrs) => addrs.clone(),
+        FanOutTargets::AllRecords => {
+            let url = Url::parse(url)?;
+            let host = url.host_str().ok_or("URL has no host")?;
+            let port = url.port_or_known_default().unwrap_or(443);
+            let mut addrs: Vec<IpAddr> = lookup_host((host, port)).await?.map(|a| a.ip()).collect();
+            addrs.sort();
+            addrs.dedup();
+            addrs
+        }
+    };
+    if addrs.is_empty() {
+        return Err("No addresses to scan".into());
+    }
+    Ok(addrs)
+}
+
+/// Scans `url` through every address in parallel, keeping the input order
+pub async fn fan_out(
+    url: &str,
+    addrs: &[IpAddr],
+    client: &SniperClient,
+    method: RequestMethod,
+    request_headers: &HeaderMap,
+    registry: &CdnRegistry,
+) -> Result<FanOutReport, Box<dyn Error + Send + Sync>> {
+    let host = Url::parse(url)?.host_str().ok_or("URL has no host")?.to_string();
+    let has_authorization = request_headers.contains_key(AUTHORIZATION);
+    let mut tasks = JoinSet::new();
+
+    for (index, ip) in addrs.iter().copied().enumerate() {
+        let pinned = client.pinned(&host, &[ip]);
+        let (url, request_headers, registry) = (url.to_string(), request_headers.clone(), registry.clone());
+        tasks.spawn(async move {
+            let scan = match pinned {
+                Ok(pinned) => match pinned.fetch_headers(&url, method, &request_headers).await {
+                    Ok(fetched) => IpScan::from_response(ip, fetched.status.as_u16(), &fetched.headers, &registry, has_authorization),
+                    Err(e) => IpScan::failed(ip, e.to_string()),
+                },
+                Err(e) => IpScan::failed(ip, e.to_string()),
+            };
+            (index, scan)
+        });
+    }
+
+    let mut scans = Vec::with_capacity(addrs.len());
+    while let Some(joined) = tasks.join_next().await {
+        scans.push(joined?);
+    }
+    scans.sort_by_key(|(index, _)| *index);
+    Ok(FanOutReport::summarize(scans.into_iter().map(|(_, scan)| scan).collect()))
+}
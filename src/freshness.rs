@@ -0,0 +1,126 @@
+use crate::cache_control::CacheDirectives;
+use reqwest::header::HeaderMap;
+use serde::Serialize;
+use std::fmt;
+use std::time::{Duration, SystemTime};
+
+/// Fraction of `Date - Last-Modified` used as a heuristic lifetime (RFC 9111 §4.2.2)
+const HEURISTIC_FRACTION: f64 = 0.1;
+
+/// Where the freshness lifetime came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FreshnessSource {
+    SMaxage,
+    MaxAge,
+    Expires,
+    Heuristic,
+}
+
+impl fmt::Display for FreshnessSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            FreshnessSource::SMaxage => "s-maxage",
+            FreshnessSource::MaxAge => "max-age",
+            FreshnessSource::Expires => "Expires",
+            FreshnessSource::Heuristic => "heuristic from Last-Modified",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Freshness of a response as seen by a shared cache (RFC 9111 §4.2)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Freshness {
+    pub lifetime_secs: Option<u64>,
+    pub source: Option<FreshnessSource>,
+    pub current_age_secs: u64,
+    /// Lifetime minus current age; negative once the response is stale
+    pub ttl_remaining_secs: Option<i64>,
+    pub is_fresh: bool,
+}
+
+/// Computes the freshness lifetime of a response received just now
+pub fn freshness_lifetime(directives: &CacheDirectives, headers: &HeaderMap) -> Option<(u64, FreshnessSource)> {
+    freshness_lifetime_at(directives, headers, SystemTime::now())
+}
+
+/// Computes the freshness lifetime, preferring s-maxage > max-age > Expires > heuristic.
+/// Without a valid Date, `response_time` stands in for it (RFC 9111 §4.2.1).
+pub fn freshness_lifetime_at(
+    directives: &CacheDirectives,
+    headers: &HeaderMap,
+    response_time: SystemTime,
+) -> Option<(u64, FreshnessSource)> {
+    if let Some(s_maxage) = directives.s_maxage {
+        return Some((s_maxage, FreshnessSource::SMaxage));
+    }
+    if let Some(max_age) = directives.max_age {
+        return Some((max_age, FreshnessSource::MaxAge));
+    }
+
+    let date = header_date(headers, "date").unwrap_or(response_time);
+    if let Some(expires) = headers.get("expires").and_then(|v| v.to_str().ok()) {
+        // An invalid Expires (e.g. "0") means already expired
+        let lifetime = match httpdate::parse_http_date(expires.trim()) {
+            Ok(expires) => secs_between(date, expires),
+            Err(_) => 0,
+        };
+        return Some((lifetime, FreshnessSource::Expires));
+    }
+
+    let last_modified = header_date(headers, "last-modified")?;
+    let lifetime = (secs_between(last_modified, date) as f64 * HEURISTIC_FRACTION) as u64;
+    Some((lifetime, FreshnessSource::Heuristic))
+}
+
+/// Computes the current age of a response (RFC 9111 §4.2.3)
+pub fn current_age(headers: &HeaderMap, request_time: SystemTime, response_time: SystemTime, now: SystemTime) -> u64 {
+    let age_value = headers
+        .get("age")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .unwrap_or(0);
+    let date_value = header_date(headers, "date").unwrap_or(response_time);
+
+    let apparent_age = secs_between(date_value, response_time);
+    let response_delay = secs_between(request_time, response_time);
+    let corrected_age_value = age_value.saturating_add(response_delay);
+    let corrected_initial_age = apparent_age.max(corrected_age_value);
+    let resident_time = secs_between(response_time, now);
+
+    corrected_initial_age.saturating_add(resident_time)
+}
+
+/// Evaluates lifetime, age and remaining TTL for a response
+pub fn evaluate(
+    directives: &CacheDirectives,
+    headers: &HeaderMap,
+    request_time: SystemTime,
+    response_time: SystemTime,
+    now: SystemTime,
+) -> Freshness {
+    let lifetime = freshness_lifetime_at(directives, headers, response_time);
+    let current_age_secs = current_age(headers, request_time, response_time, now);
+    let ttl_remaining_secs = lifetime.map(|(l, _)| l as i64 - current_age_secs as i64);
+
+    Freshness {
+        lifetime_secs: lifetime.map(|(l, _)| l),
+        source: lifetime.map(|(_, s)| s),
+        current_age_secs,
+        ttl_remaining_secs,
+        is_fresh: ttl_remaining_secs.is_some_and(|ttl| ttl > 0),
+    }
+}
+
+fn header_date(headers: &HeaderMap, name: &str) -> Option<SystemTime> {
+    headers
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| httpdate::parse_http_date(v.trim()).ok())
+}
+
+/// Whole seconds from `earlier` to `later`, or 0 if `later` is not after `earlier`
+fn secs_between(earlier: SystemTime, later: SystemTime) -> u64 {
+    later.duration_since(earlier).unwrap_or(Duration::ZERO).as_secs()
+}
@@ -1,9 +1,672 @@
-use reqwest::{Client, header::HeaderMap};
+use crate::error::{ErrorKind, ScanError};
+use crate::timing::{self, ConnectionTimings};
+use reqwest::header::{HeaderMap, AUTHORIZATION, COOKIE, LOCATION, PROXY_AUTHORIZATION, RANGE};
+use reqwest::{redirect, Certificate, Client, ClientBuilder, Method, Proxy, StatusCode, Url};
+use serde::Serialize;
 use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::future::Future;
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
 
-/// Fetches headers from the given URL
-pub async fn fetch_headers(url: &str) -> Result<HeaderMap, Box<dyn Error + Send + Sync>> {
-    let client = Client::new();
-    let response = client.get(url).send().await?;
-    Ok(response.headers().clone())
+/// Headers of a response together with when it was requested and received
+#[derive(Debug)]
+pub struct FetchedHeaders {
+    /// URL that produced the response, after any redirects
+    pub url: String,
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub request_time: SystemTime,
+    /// When the response headers arrived
+    pub response_time: SystemTime,
+    /// When the body was fully read; equals `response_time` if it was not read
+    pub completed_time: SystemTime,
+}
+
+/// HTTP version the client negotiates
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HttpVersion {
+    /// HTTP/2 via ALPN when the server offers it, HTTP/1.1 otherwise
+    #[default]
+    Auto,
+    Http1Only,
+    /// HTTP/2 without negotiation (prior knowledge)
+    Http2Only,
+}
+
+/// How a scan requests the URL
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RequestMethod {
+    Head,
+    #[default]
+    Get,
+    /// GET for the first byte only (`Range: bytes=0-0`)
+    GetRange,
+}
+
+impl fmt::Display for RequestMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            RequestMethod::Head => "HEAD",
+            RequestMethod::Get => "GET",
+            RequestMethod::GetRange => "GET (Range)",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for RequestMethod {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "head" => Ok(RequestMethod::Head),
+            "get" => Ok(RequestMethod::Get),
+            "range" | "get-range" => Ok(RequestMethod::GetRange),
+            other => Err(format!("unknown method '{}' (expected HEAD, GET or RANGE)", other)),
+        }
+    }
+}
+
+/// curl-style `--resolve host:port:addr[,addr]`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolveOverride {
+    pub host: String,
+    /// Only URLs with this port are overridden; `0` overrides every port
+    pub port: u16,
+    pub addrs: Vec<IpAddr>,
+    /// Port to connect to instead of the URL's; only a `--connect-to` sets it
+    pub target_port: Option<u16>,
+}
+
+impl ResolveOverride {
+    fn applies_to(&self, port: u16) -> bool {
+        self.port == 0 || self.port == port
+    }
+
+    fn applies_to_url(&self, url: &Url) -> bool {
+        let host = url.host_str().map(|h| h.trim_start_matches('[').trim_end_matches(']').to_ascii_lowercase());
+        host.as_deref() == Some(self.host.as_str()) && url.port_or_known_default().is_some_and(|p| self.applies_to(p))
+    }
+}
+
+impl FromStr for ResolveOverride {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (host, rest) = s.split_once(':').ok_or_else(|| format!("expected host:port:addr, got '{}'", s))?;
+        let (port, addrs) = rest.split_once(':').ok_or_else(|| format!("expected host:port:addr, got '{}'", s))?;
+        let port = port.parse().map_err(|_| format!("invalid port '{}'", port))?;
+        let addrs = addrs
+            .split(',')
+            .map(|a| a.trim().trim_start_matches('[').trim_end_matches(']'))
+            .map(|a| a.parse::<IpAddr>().map_err(|_| format!("invalid address '{}'", a)))
+            .collect::<Result<Vec<_>, _>>()?;
+        if host.is_empty() {
+            return Err("host must not be empty".to_string());
+        }
+        Ok(ResolveOverride { host: host.to_ascii_lowercase(), port, addrs, target_port: None })
+    }
+}
+
+/// curl-style `--connect-to host:port:target_host:target_port`; empty ports
+/// mean "any" and "unchanged"
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConnectTo {
+    pub host: String,
+    pub port: Option<u16>,
+    pub target_host: String,
+    pub target_port: Option<u16>,
+}
+
+impl ConnectTo {
+    /// Looks up the target host once and turns it into the equivalent `--resolve`,
+    /// plus the target port if it differs from the source port
+    pub async fn lookup(&self) -> Result<ResolveOverride, String> {
+        let addrs: Vec<IpAddr> = tokio::net::lookup_host((self.target_host.as_str(), 0))
+            .await
+            .map_err(|e| format!("Cannot resolve {}: {}", self.target_host, e))?
+            .map(|addr| addr.ip())
+            .collect();
+        Ok(ResolveOverride {
+            host: self.host.clone(),
+            port: self.port.unwrap_or(0),
+            addrs,
+            target_port: self.target_port.filter(|&to| self.port != Some(to)),
+        })
+    }
+}
+
+impl FromStr for ConnectTo {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let fields = split_host_fields(s);
+        let [host, port, target_host, target_port] = fields.as_slice() else {
+            return Err(format!("expected host:port:target_host:target_port, got '{}'", s));
+        };
+        let parse_port = |p: &str| -> Result<Option<u16>, String> {
+            if p.is_empty() { Ok(None) } else { p.parse().map(Some).map_err(|_| format!("invalid port '{}'", p)) }
+        };
+        if host.is_empty() || target_host.is_empty() {
+            return Err("host and target host must not be empty".to_string());
+        }
+        Ok(ConnectTo {
+            host: host.to_ascii_lowercase(),
+            port: parse_port(port)?,
+            target_host: target_host.trim_start_matches('[').trim_end_matches(']').to_string(),
+            target_port: parse_port(target_port)?,
+        })
+    }
+}
+
+/// Splits on ':' outside of `[...]` so IPv6 targets can be given in brackets
+fn split_host_fields(s: &str) -> Vec<&str> {
+    let mut fields = Vec::new();
+    let mut start = 0;
+    let mut in_brackets = false;
+    for (i, c) in s.char_indices() {
+        match c {
+            '[' => in_brackets = true,
+            ']' => in_brackets = false,
+            ':' if !in_brackets => {
+                fields.push(&s[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    fields.push(&s[start..]);
+    fields
+}
+
+/// HTTP client shared by every scan so connections are pooled
+#[derive(Clone)]
+pub struct SniperClient {
+    clients: Clients,
+    /// Clients for each port named by a `ResolveOverride` without a target port;
+    /// they also apply the overrides for that port, which reqwest's resolver
+    /// cannot tell apart
+    port_clients: Vec<(u16, Clients)>,
+    settings: SniperClientBuilder,
+}
+
+#[derive(Clone)]
+struct Clients {
+    client: Client,
+    /// Same settings as `client` but never follows redirects
+    manual_client: Client,
+}
+
+impl SniperClient {
+    pub fn builder() -> SniperClientBuilder {
+        SniperClientBuilder::default()
+    }
+
+    /// A client with the same settings whose connections to `host` go to `addrs`
+    pub fn pinned(&self, host: &str, addrs: &[IpAddr]) -> Result<SniperClient, Box<dyn Error + Send + Sync>> {
+        self.settings
+            .clone()
+            .resolve(ResolveOverride { host: host.to_string(), port: 0, addrs: addrs.to_vec(), target_port: None })
+            .build()
+    }
+
+    /// The clients whose address overrides apply to the port of `url`
+    fn clients_for(&self, url: &str) -> &Clients {
+        let port = Url::parse(url).ok().and_then(|url| url.port_or_known_default());
+        self.port_clients
+            .iter()
+            .find(|(p, _)| Some(*p) == port)
+            .map_or(&self.clients, |(_, clients)| clients)
+    }
+
+    /// The same client with retries disabled
+    pub fn without_retries(&self) -> SniperClient {
+        let mut client = self.clone();
+        client.settings.retry.max_retries = 0;
+        client
+    }
+
+    /// Fetches headers from the given URL, sending `request_headers` along
+    pub async fn fetch_headers(
+        &self,
+        url: &str,
+        method: RequestMethod,
+        request_headers: &HeaderMap,
+    ) -> Result<FetchedHeaders, ScanError> {
+        let retry = &self.settings.retry;
+        retrying(retry, || fetch_once(&self.clients_for(url).client, url, method, request_headers, false), |fetched| fetched.status).await
+    }
+
+    /// Like `fetch_headers`, but also downloads the body so the total time is known
+    pub async fn fetch_timed(
+        &self,
+        url: &str,
+        method: RequestMethod,
+        request_headers: &HeaderMap,
+    ) -> Result<FetchedHeaders, ScanError> {
+        let retry = &self.settings.retry;
+        retrying(retry, || fetch_once(&self.clients_for(url).client, url, method, request_headers, true), |fetched| fetched.status).await
+    }
+
+    /// Times DNS, connect and TLS on a separate connection to the host of `url`,
+    /// honoring --resolve and --connect-to. Skipped behind a proxy.
+    pub async fn connection_timings(&self, url: &str) -> ConnectionTimings {
+        let Ok(url) = Url::parse(url) else {
+            return ConnectionTimings::default();
+        };
+        let (Some(host), Some(port)) = (url.host_str(), url.port_or_known_default()) else {
+            return ConnectionTimings::default();
+        };
+        if self.settings.proxy.is_some() {
+            return ConnectionTimings::default();
+        }
+        let host = host.trim_start_matches('[').trim_end_matches(']').to_ascii_lowercase();
+
+        let (connect_host, connect_port) = match self.settings.resolve.iter().rev().find(|r| r.host == host && r.applies_to(port)) {
+            Some(resolve) => (
+                resolve.addrs.first().map(IpAddr::to_string).unwrap_or_else(|| host.clone()),
+                resolve.target_port.unwrap_or(port),
+            ),
+            None => (host.clone(), port),
+        };
+        let limit = self.settings.connect_timeout.unwrap_or(Duration::from_secs(10));
+        timing::probe(&host, &connect_host, connect_port, url.scheme() == "https", limit).await
+    }
+
+    /// Fetches the response and its body with GET
+    pub async fn fetch_body(&self, url: &str, request_headers: &HeaderMap) -> Result<(FetchedHeaders, String), ScanError> {
+        let retry = &self.settings.retry;
+        let attempt = || async {
+            let request_time = SystemTime::now();
+            let response = self.clients_for(url).client.get(url).headers(request_headers.clone()).send().await?;
+            let (url, status, headers) = (response.url().to_string(), response.status(), response.headers().clone());
+            let response_time = SystemTime::now();
+            let body = response.text().await?;
+            let fetched = FetchedHeaders { url, status, headers, request_time, response_time, completed_time: SystemTime::now() };
+            Ok((fetched, body))
+        };
+        retrying(retry, attempt, |(fetched, _)| fetched.status).await
+    }
+
+    /// Sends a single request with any method, e.g. `PURGE`; never retried
+    pub async fn send(&self, method: Method, url: &str, request_headers: &HeaderMap) -> Result<FetchedHeaders, ScanError> {
+        let request_time = SystemTime::now();
+        let response = self.clients_for(url).client.request(method, url).headers(request_headers.clone()).send().await?;
+        let response_time = SystemTime::now();
+        Ok(FetchedHeaders {
+            url: response.url().to_string(),
+            status: response.status(),
+            headers: response.headers().clone(),
+            request_time,
+            response_time,
+            completed_time: response_time,
+        })
+    }
+
+    /// Follows redirects one hop at a time and returns every response, the
+    /// final one last. Stops at the redirect limit instead of failing.
+    /// Like reqwest's own redirect policy, credentials are only sent to the
+    /// scheme, host and port of `url`.
+    pub async fn fetch_chain(
+        &self,
+        url: &str,
+        method: RequestMethod,
+        request_headers: &HeaderMap,
+    ) -> Result<Vec<FetchedHeaders>, ScanError> {
+        let retry = &self.settings.retry;
+        let mut hops = Vec::new();
+        let mut current = Url::parse(url).map_err(|e| ScanError::new(ErrorKind::InvalidUrl, e.to_string()))?;
+        let origin = current.origin();
+        let mut anonymous = request_headers.clone();
+        for name in [AUTHORIZATION, COOKIE, PROXY_AUTHORIZATION] {
+            anonymous.remove(name);
+        }
+
+        loop {
+            let hop_headers = if current.origin() == origin { request_headers } else { &anonymous };
+            let attempt = || fetch_once(&self.clients_for(current.as_str()).manual_client, current.as_str(), method, hop_headers, true);
+            let hop = retrying(retry, attempt, |fetched| fetched.status).await?;
+            let next = redirect_target(&current, &hop);
+            hops.push(hop);
+
+            match next {
+                Some(next) if hops.len() <= self.settings.max_redirects => current = next,
+                _ => return Ok(hops),
+            }
+        }
+    }
+}
+
+/// When and how often failed requests are repeated
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Attempts after the first one; `0` disables retries
+    pub max_retries: u32,
+    /// Delay before the first retry, doubled for each further one
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy { max_retries: 2, base_delay: Duration::from_millis(250), max_delay: Duration::from_secs(5) }
+    }
+}
+
+impl RetryPolicy {
+    /// Responses that say "try again later" rather than describe the resource
+    pub const RETRY_STATUSES: [u16; 4] = [429, 502, 503, 504];
+
+    /// Exponential delay before retry number `retry` (1-based), without jitter
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let factor = 2u32.saturating_pow(retry.saturating_sub(1));
+        self.base_delay.saturating_mul(factor).min(self.max_delay)
+    }
+
+    /// Backoff with "equal jitter": a random delay between half and all of it,
+    /// so parallel scans do not retry in lockstep
+    pub fn delay(&self, retry: u32) -> Duration {
+        let backoff = self.backoff(retry);
+        let half = backoff / 2;
+        half + half.mul_f64(rand::random::<f64>())
+    }
+
+    fn retries_status(status: StatusCode) -> bool {
+        Self::RETRY_STATUSES.contains(&status.as_u16())
+    }
+}
+
+/// Runs `attempt` until it succeeds, fails permanently or runs out of retries.
+/// A response with a retry status is repeated too, but the last one is returned
+/// as is so its headers can still be analyzed.
+async fn retrying<T, F, Fut, S>(policy: &RetryPolicy, mut attempt: F, status: S) -> Result<T, ScanError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, ScanError>>,
+    S: Fn(&T) -> StatusCode,
+{
+    let mut attempts = 0;
+    loop {
+        attempts += 1;
+        match attempt().await {
+            Ok(value) if RetryPolicy::retries_status(status(&value)) && attempts <= policy.max_retries => {
+                tokio::time::sleep(policy.delay(attempts)).await;
+            }
+            Ok(value) => return Ok(value),
+            Err(e) if e.kind.is_transient() && attempts <= policy.max_retries => {
+                tokio::time::sleep(policy.delay(attempts)).await;
+            }
+            Err(e) => return Err(e.with_attempts(attempts)),
+        }
+    }
+}
+
+/// Sends one request; with `read_body` the body is downloaded and discarded
+async fn fetch_once(
+    client: &Client,
+    url: &str,
+    method: RequestMethod,
+    request_headers: &HeaderMap,
+    read_body: bool,
+) -> Result<FetchedHeaders, ScanError> {
+    let request = match method {
+        RequestMethod::Head => client.head(url),
+        RequestMethod::Get => client.get(url),
+        RequestMethod::GetRange => client.get(url).header(RANGE, "bytes=0-0"),
+    };
+    let request_time = SystemTime::now();
+    let mut response = request.headers(request_headers.clone()).send().await?;
+    let response_time = SystemTime::now();
+    let (url, status, headers) = (response.url().to_string(), response.status(), response.headers().clone());
+
+    let mut completed_time = response_time;
+    if read_body {
+        while response.chunk().await?.is_some() {}
+        completed_time = SystemTime::now();
+    }
+
+    Ok(FetchedHeaders { url, status, headers, request_time, response_time, completed_time })
+}
+
+/// Starts a local proxy whose connections all go to `addrs`, so a `--connect-to`
+/// can change the port while the URL, `Host` and SNI stay the same. HTTPS
+/// arrives as a CONNECT tunnel; plain HTTP requests are passed on unchanged,
+/// in the absolute form servers must accept. Returns the proxy's URL.
+fn spawn_tunnel(addrs: Vec<SocketAddr>, connect_timeout: Duration) -> io::Result<Url> {
+    let runtime = tokio::runtime::Handle::try_current()
+        .map_err(|_| io::Error::other("--connect-to with another port needs a tokio runtime"))?;
+    let listener = std::net::TcpListener::bind(("127.0.0.1", 0))?;
+    listener.set_nonblocking(true)?;
+    let proxy_url = Url::parse(&format!("http://{}", listener.local_addr()?)).expect("socket address is a valid URL host");
+    let listener = {
+        let _guard = runtime.enter();
+        TcpListener::from_std(listener)?
+    };
+
+    runtime.spawn(async move {
+        while let Ok((client, _)) = listener.accept().await {
+            let addrs = addrs.clone();
+            tokio::spawn(async move {
+                // A failure just closes the connection, which reqwest reports as a connect error
+                let _ = tunnel(client, &addrs, connect_timeout).await;
+            });
+        }
+    });
+    Ok(proxy_url)
+}
+
+/// Serves one proxy connection of `spawn_tunnel`
+async fn tunnel(mut client: TcpStream, addrs: &[SocketAddr], connect_timeout: Duration) -> io::Result<()> {
+    let mut head = Vec::new();
+    let mut buf = [0u8; 4096];
+    while !head.windows(4).any(|w| w == b"\r\n\r\n") {
+        let read = client.read(&mut buf).await?;
+        if read == 0 || head.len() > 64 * 1024 {
+            return Ok(());
+        }
+        head.extend_from_slice(&buf[..read]);
+    }
+
+    let mut upstream = tokio::time::timeout(connect_timeout, TcpStream::connect(addrs))
+        .await
+        .map_err(|_| io::Error::new(io::ErrorKind::TimedOut, "connect timed out"))??;
+    if head.starts_with(b"CONNECT ") {
+        client.write_all(b"HTTP/1.1 200 Connection Established\r\n\r\n").await?;
+    } else {
+        upstream.write_all(&head).await?;
+    }
+    tokio::io::copy_bidirectional(&mut client, &mut upstream).await?;
+    Ok(())
+}
+
+/// Resolves the `Location` of a redirect response against the request URL
+fn redirect_target(current: &Url, hop: &FetchedHeaders) -> Option<Url> {
+    if !hop.status.is_redirection() || hop.status == StatusCode::NOT_MODIFIED {
+        return None;
+    }
+    let location = hop.headers.get(LOCATION)?.to_str().ok()?;
+    current.join(location).ok()
+}
+
+impl Default for SniperClient {
+    fn default() -> Self {
+        SniperClient::builder().build().expect("default HTTP client settings are valid")
+    }
+}
+
+/// Settings for a `SniperClient`
+#[derive(Debug, Clone)]
+pub struct SniperClientBuilder {
+    timeout: Option<Duration>,
+    connect_timeout: Option<Duration>,
+    user_agent: String,
+    max_redirects: usize,
+    proxy: Option<String>,
+    root_certificates: Vec<PathBuf>,
+    insecure: bool,
+    http_version: HttpVersion,
+    resolve: Vec<ResolveOverride>,
+    retry: RetryPolicy,
+}
+
+impl Default for SniperClientBuilder {
+    fn default() -> Self {
+        SniperClientBuilder {
+            timeout: Some(Duration::from_secs(30)),
+            connect_timeout: Some(Duration::from_secs(10)),
+            user_agent: concat!("cache_sniper/", env!("CARGO_PKG_VERSION")).to_string(),
+            max_redirects: 10,
+            proxy: None,
+            root_certificates: Vec::new(),
+            insecure: false,
+            http_version: HttpVersion::Auto,
+            resolve: Vec::new(),
+            retry: RetryPolicy::default(),
+        }
+    }
+}
+
+impl SniperClientBuilder {
+    /// Total time allowed per request; `None` waits forever
+    pub fn timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn connect_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.connect_timeout = timeout;
+        self
+    }
+
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = user_agent.into();
+        self
+    }
+
+    /// Redirects followed before giving up; `0` returns the redirect response itself
+    pub fn max_redirects(mut self, max_redirects: usize) -> Self {
+        self.max_redirects = max_redirects;
+        self
+    }
+
+    /// Proxy URL used for every scheme, e.g. `http://127.0.0.1:8080`
+    pub fn proxy(mut self, proxy: impl Into<String>) -> Self {
+        self.proxy = Some(proxy.into());
+        self
+    }
+
+    /// Trusts the PEM certificate(s) in `path` in addition to the system roots
+    pub fn root_certificate(mut self, path: impl Into<PathBuf>) -> Self {
+        self.root_certificates.push(path.into());
+        self
+    }
+
+    /// Accepts invalid certificates and host names
+    pub fn insecure(mut self, insecure: bool) -> Self {
+        self.insecure = insecure;
+        self
+    }
+
+    pub fn http_version(mut self, version: HttpVersion) -> Self {
+        self.http_version = version;
+        self
+    }
+
+    /// Sends connections for a host and port to fixed addresses instead of
+    /// asking DNS; `ConnectTo::lookup` turns a `--connect-to` into one of these
+    pub fn resolve(mut self, resolve: ResolveOverride) -> Self {
+        self.resolve.push(resolve);
+        self
+    }
+
+    pub fn retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Builds the client, failing on an unreadable certificate or invalid proxy URL
+    pub fn build(self) -> Result<SniperClient, Box<dyn Error + Send + Sync>> {
+        let redirect_policy = || {
+            if self.max_redirects == 0 {
+                redirect::Policy::none()
+            } else {
+                redirect::Policy::limited(self.max_redirects)
+            }
+        };
+
+        // Overrides that change the port go through a tunnel, checked last to first
+        let connect_timeout = self.connect_timeout.unwrap_or(Duration::from_secs(10));
+        let tunnels = self
+            .resolve
+            .iter()
+            .rev()
+            .filter_map(|r| r.target_port.map(|to| (r, to)))
+            .map(|(r, to)| {
+                let addrs = r.addrs.iter().map(|ip| SocketAddr::new(*ip, to)).collect();
+                Ok((r.clone(), spawn_tunnel(addrs, connect_timeout)?))
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+
+        let clients = |port| -> Result<Clients, Box<dyn Error + Send + Sync>> {
+            Ok(Clients {
+                client: self.client_builder(port, &tunnels)?.redirect(redirect_policy()).build()?,
+                manual_client: self.client_builder(port, &tunnels)?.redirect(redirect::Policy::none()).build()?,
+            })
+        };
+        let mut ports: Vec<u16> =
+            self.resolve.iter().filter(|r| r.target_port.is_none()).map(|r| r.port).filter(|&p| p != 0).collect();
+        ports.sort_unstable();
+        ports.dedup();
+
+        Ok(SniperClient {
+            clients: clients(0)?,
+            port_clients: ports.into_iter().map(|p| Ok((p, clients(p)?))).collect::<Result<_, Box<dyn Error + Send + Sync>>>()?,
+            settings: self,
+        })
+    }
+
+    /// Applies every setting except the redirect policy, with the address
+    /// overrides for `port` (`0`: only those for every port), and the tunnels
+    /// of the overrides that change the port
+    fn client_builder(&self, port: u16, tunnels: &[(ResolveOverride, Url)]) -> Result<ClientBuilder, Box<dyn Error + Send + Sync>> {
+        let mut builder = Client::builder()
+            .user_agent(self.user_agent.as_str())
+            .danger_accept_invalid_certs(self.insecure);
+
+        // Added before --proxy so they take precedence
+        for (resolve, tunnel) in tunnels {
+            let (resolve, tunnel) = (resolve.clone(), tunnel.clone());
+            builder = builder.proxy(Proxy::custom(move |url| resolve.applies_to_url(url).then(|| tunnel.clone())));
+        }
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        if let Some(proxy) = &self.proxy {
+            builder = builder.proxy(Proxy::all(proxy.as_str())?);
+        }
+        for path in &self.root_certificates {
+            let pem = fs::read(path).map_err(|e| format!("Cannot read CA certificate {}: {}", path.display(), e))?;
+            builder = builder.add_root_certificate(Certificate::from_pem(&pem)?);
+        }
+        for resolve in self.resolve.iter().filter(|r| r.target_port.is_none() && r.applies_to(port)) {
+            let addrs: Vec<SocketAddr> = resolve.addrs.iter().map(|ip| SocketAddr::new(*ip, resolve.port)).collect();
+            builder = builder.resolve_to_addrs(&resolve.host, &addrs);
+        }
+        Ok(match self.http_version {
+            HttpVersion::Auto => builder,
+            HttpVersion::Http1Only => builder.http1_only(),
+            HttpVersion::Http2Only => builder.http2_prior_knowledge(),
+        })
+    }
 }
@@ -1,6 +1,25 @@
-// Library exports for testing
+// Library exports, used by the binary and the tests
 pub mod cache_checker;
+pub mod cache_control;
+pub mod cache_status;
+pub mod cdn;
+pub mod credentials;
+pub mod error;
+pub mod cacheability;
+pub mod fan_out;
+pub mod freshness;
 pub mod validate_cache;
 pub mod http_client;
+pub mod timing;
 pub mod utils;
+pub mod vary;
+pub mod verdict;
 pub mod metrics;
+pub mod method_comparison;
+pub mod origin_comparison;
+pub mod purge;
+pub mod query_key;
+pub mod redirects;
+pub mod revalidation;
+pub mod stale;
+pub mod warmup;
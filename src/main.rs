@@ -1,43 +1,285 @@
-mod cache_checker;
-mod validate_cache;
-mod http_client;
-mod utils;
-mod metrics;
-
-use clap::Parser;
-use cache_checker::check_cache;
-use validate_cache::validate_cache;
+use clap::{Parser, Subcommand};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::Method;
+use cache_sniper::{credentials, metrics, purge, stale};
+use cache_sniper::cache_checker::{check_cache, ScanOptions};
+use cache_sniper::error::ScanError;
+use cache_sniper::fan_out::FanOutTargets;
+use cache_sniper::http_client::{ConnectTo, HttpVersion, RequestMethod, ResolveOverride, RetryPolicy, SniperClient};
+use cache_sniper::purge::{PollPlan, PurgeStep};
+use cache_sniper::stale::StaleWindows;
+use cache_sniper::validate_cache::validate_cache;
+use cache_sniper::warmup::WarmupPlan;
 use tokio::sync::mpsc;
 use std::error::Error;
 use std::fs;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
 
 /// CLI arguments
 #[derive(Parser)]
 #[command(author, version, about)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Target URLs to check (accepts single or multiple URLs)
-    #[arg(short = 'u', long = "url", alias = "urls", num_args = 1..)]
+    #[arg(short = 'u', long = "url", alias = "urls", num_args = 1.., global = true)]
     urls: Vec<String>,
 
     /// Enable JSON output
-    #[arg(short, long)]
+    #[arg(short, long, global = true)]
     json: bool,
 
+    /// Print every response header of each scan
+    #[arg(short, long, conflicts_with = "json")]
+    verbose: bool,
+
     /// Enable cache validation (checks if revalidation works correctly)
     #[arg(long)]
     validate: bool,
 
+    /// With validation, also replay ETag / Last-Modified as conditional requests and expect 304
+    #[arg(long)]
+    revalidate: bool,
+
+    /// Send N sequential requests first and report how many it takes to get a HIT
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    warmup: Option<u32>,
+
+    /// Delay between warm-up requests in milliseconds
+    #[arg(long, default_value_t = 1000)]
+    warmup_delay_ms: u64,
+
     /// Start Prometheus metrics server
     #[arg(long)]
     metrics: bool,
 
     /// Save output to a JSON file
-    #[arg(short, long)]
+    #[arg(short, long, global = true)]
     output: Option<String>,
 
     /// Exit with code 1 if any URL has no caching enabled
-    #[arg(long)]
+    #[arg(long, global = true)]
     exit_code: bool,
+
+    /// Send Akamai debug pragmas (X-Cache, X-Check-Cacheable, X-Cache-Key)
+    #[arg(long)]
+    akamai_debug: bool,
+
+    /// Re-request with changed values of each Vary field to check which ones the edge keys on
+    #[arg(long)]
+    vary_probe: bool,
+
+    /// Re-request with random, reordered and UTM query parameters to check if the query is part of the cache key
+    #[arg(long)]
+    query_probe: bool,
+
+    /// Follow redirects hop by hop and report cache headers for each hop
+    #[arg(long)]
+    trace_redirects: bool,
+
+    /// Request method: HEAD, GET or RANGE (GET for the first byte only)
+    #[arg(long, default_value = "GET")]
+    method: RequestMethod,
+
+    /// Also request with HEAD and GET and report differing cache headers or CDN status
+    #[arg(long)]
+    compare_methods: bool,
+
+    /// Extra request header as "Name: value" (repeatable)
+    #[arg(short = 'H', long = "header", value_parser = credentials::parse_header_line)]
+    headers: Vec<(HeaderName, HeaderValue)>,
+
+    /// Cookie to send, e.g. "session=abc" (repeatable)
+    #[arg(long = "cookie")]
+    cookies: Vec<String>,
+
+    /// Send "Authorization: Bearer <TOKEN>"
+    #[arg(long, value_parser = credentials::bearer)]
+    bearer: Option<HeaderValue>,
+
+    /// Send HTTP basic auth as "user:password"
+    #[arg(long, value_parser = credentials::basic_auth, conflicts_with = "bearer")]
+    basic_auth: Option<HeaderValue>,
+
+    /// Connect to ADDR for HOST on PORT only, as "host:port:addr[,addr]" (repeatable)
+    #[arg(long)]
+    resolve: Vec<ResolveOverride>,
+
+    /// Connect to TARGET_HOST instead of HOST, as "host:port:target_host:target_port" (repeatable)
+    #[arg(long)]
+    connect_to: Vec<ConnectTo>,
+
+    /// Also fetch each URL directly from this origin IP and diff the cache headers
+    #[arg(long)]
+    origin: Option<IpAddr>,
+
+    /// Scan each URL through every A/AAAA address of its host
+    #[arg(long, conflicts_with = "ips")]
+    all_ips: bool,
+
+    /// Scan each URL through these addresses (comma-separated)
+    #[arg(long, value_delimiter = ',')]
+    ips: Vec<IpAddr>,
+
+    /// Request timeout in seconds (0 disables it)
+    #[arg(long, default_value_t = 30)]
+    timeout: u64,
+
+    /// Connect timeout in seconds (0 disables it)
+    #[arg(long, default_value_t = 10)]
+    connect_timeout: u64,
+
+    /// User-Agent sent with every request
+    #[arg(long)]
+    user_agent: Option<String>,
+
+    /// Maximum number of redirects to follow (0 disables following)
+    #[arg(long, default_value_t = 10)]
+    max_redirects: usize,
+
+    /// Proxy URL for all requests, e.g. http://127.0.0.1:8080
+    #[arg(long)]
+    proxy: Option<String>,
+
+    /// Retries for transient failures (DNS, connect, timeout, 429/502/503/504)
+    #[arg(long, default_value_t = 2)]
+    retries: u32,
+
+    /// Delay before the first retry in milliseconds, doubled for each further retry
+    #[arg(long, default_value_t = 250)]
+    retry_delay_ms: u64,
+
+    /// Extra PEM root certificate to trust (repeatable)
+    #[arg(long = "cacert")]
+    ca_certs: Vec<String>,
+
+    /// Accept invalid TLS certificates
+    #[arg(short = 'k', long)]
+    insecure: bool,
+
+    /// HTTP version preference
+    #[arg(long, default_value = "auto", value_parser = ["auto", "1.1", "2"])]
+    http_version: String,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Purge each URL, then poll until every edge serves a fresh object
+    PurgeVerify(PurgeVerifyArgs),
+    /// Check stale-while-revalidate and stale-if-error through a cache in front of a local origin
+    StaleCheck(StaleCheckArgs),
+}
+
+#[derive(clap::Args)]
+struct PurgeVerifyArgs {
+    /// Shell command that purges the URL, which it finds in $CACHE_SNIPER_URL
+    #[arg(long, required_unless_present = "purge_method", conflicts_with = "purge_method")]
+    purge_command: Option<String>,
+
+    /// Purge by sending this HTTP method instead, e.g. PURGE or BAN
+    #[arg(long, value_parser = purge::parse_method)]
+    purge_method: Option<Method>,
+
+    /// Where to send --purge-method (default: the URL being verified)
+    #[arg(long, requires = "purge_method")]
+    purge_url: Option<String>,
+
+    /// Delay between polls in milliseconds
+    #[arg(long, default_value_t = 1000)]
+    poll_interval_ms: u64,
+
+    /// Give up on edges still serving the old object after this many seconds
+    #[arg(long, default_value_t = 60)]
+    deadline: u64,
+}
+
+#[derive(clap::Args)]
+struct StaleCheckArgs {
+    /// Address of the controllable origin; the cache given with --url must forward every path to it
+    #[arg(long, default_value = "127.0.0.1:8787")]
+    origin_listen: SocketAddr,
+
+    /// max-age advertised by the origin, in seconds
+    #[arg(long, default_value_t = 2)]
+    max_age: u64,
+
+    /// stale-while-revalidate window, in seconds
+    #[arg(long, default_value_t = 4)]
+    stale_while_revalidate: u64,
+
+    /// stale-if-error window, in seconds
+    #[arg(long, default_value_t = 4)]
+    stale_if_error: u64,
+}
+
+impl StaleCheckArgs {
+    fn windows(&self) -> StaleWindows {
+        StaleWindows {
+            max_age: self.max_age,
+            stale_while_revalidate: self.stale_while_revalidate,
+            stale_if_error: self.stale_if_error,
+        }
+    }
+}
+
+impl PurgeVerifyArgs {
+    fn step(&self) -> PurgeStep {
+        match (&self.purge_command, &self.purge_method) {
+            (Some(command), _) => PurgeStep::Command(command.clone()),
+            (None, Some(method)) => PurgeStep::Request { method: method.clone(), url: self.purge_url.clone() },
+            (None, None) => unreachable!("clap requires --purge-command or --purge-method"),
+        }
+    }
+
+    fn plan(&self) -> PollPlan {
+        PollPlan { interval: Duration::from_millis(self.poll_interval_ms), deadline: Duration::from_secs(self.deadline) }
+    }
+}
+
+/// Collects --header, --cookie, --bearer and --basic-auth into request headers
+fn extra_headers(args: &Args) -> Result<HeaderMap, Box<dyn Error + Send + Sync>> {
+    Ok(credentials::request_headers(&args.headers, &args.cookies, args.bearer.as_ref().or(args.basic_auth.as_ref()))?)
+}
+
+/// Builds the HTTP client shared by every scan
+async fn build_client(args: &Args) -> Result<SniperClient, Box<dyn Error + Send + Sync>> {
+    let seconds = |s: u64| (s > 0).then(|| Duration::from_secs(s));
+    let http_version = match args.http_version.as_str() {
+        "1.1" => HttpVersion::Http1Only,
+        "2" => HttpVersion::Http2Only,
+        _ => HttpVersion::Auto,
+    };
+
+    let mut builder = SniperClient::builder()
+        .timeout(seconds(args.timeout))
+        .connect_timeout(seconds(args.connect_timeout))
+        .max_redirects(args.max_redirects)
+        .insecure(args.insecure)
+        .http_version(http_version)
+        .retry(RetryPolicy {
+            max_retries: args.retries,
+            base_delay: Duration::from_millis(args.retry_delay_ms),
+            ..RetryPolicy::default()
+        });
+    if let Some(user_agent) = &args.user_agent {
+        builder = builder.user_agent(user_agent);
+    }
+    if let Some(proxy) = &args.proxy {
+        builder = builder.proxy(proxy);
+    }
+    for path in &args.ca_certs {
+        builder = builder.root_certificate(path);
+    }
+    for resolve in &args.resolve {
+        builder = builder.resolve(resolve.clone());
+    }
+    for connect_to in &args.connect_to {
+        builder = builder.resolve(connect_to.lookup().await?);
+    }
+    builder.build()
 }
 
 #[tokio::main]
@@ -51,17 +293,89 @@ async fn main() {
         return;
     }
 
+    let client = match build_client(&args).await {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("Invalid HTTP client settings: {}", e);
+            std::process::exit(2);
+        }
+    };
+
+    let extra_headers = match extra_headers(&args) {
+        Ok(headers) => headers,
+        Err(e) => {
+            eprintln!("Invalid request headers: {}", e);
+            std::process::exit(2);
+        }
+    };
+
+    let scan_options = Arc::new(ScanOptions {
+        client,
+        extra_headers,
+        origin_ip: args.origin,
+        fan_out: if args.all_ips {
+            Some(FanOutTargets::AllRecords)
+        } else if !args.ips.is_empty() {
+            Some(FanOutTargets::Addresses(args.ips.clone()))
+        } else {
+            None
+        },
+        warmup: args.warmup.map(|requests| WarmupPlan { requests, delay: Duration::from_millis(args.warmup_delay_ms) }),
+        akamai_debug: args.akamai_debug,
+        vary_probe: args.vary_probe,
+        query_probe: args.query_probe,
+        trace_redirects: args.trace_redirects,
+        method: args.method,
+        verbose: args.verbose,
+        compare_methods: args.compare_methods,
+        ..ScanOptions::default()
+    });
+
     let (tx, mut rx) = mpsc::channel(args.urls.len().max(1)); // Ensure buffer size is at least 1
 
+    let purge = match &args.command {
+        Some(Command::PurgeVerify(purge_args)) => {
+            if args.urls.is_empty() {
+                eprintln!("purge-verify needs at least one --url to purge and verify");
+                std::process::exit(2);
+            }
+            Some((purge_args.step(), purge_args.plan()))
+        }
+        _ => None,
+    };
+    let stale_check = match &args.command {
+        Some(Command::StaleCheck(stale_args)) => {
+            // Every run drives the same origin, so runs cannot overlap
+            if args.urls.len() != 1 {
+                eprintln!("stale-check takes exactly one --url: the cache in front of the origin");
+                std::process::exit(2);
+            }
+            Some((stale_args.origin_listen, stale_args.windows()))
+        }
+        _ => None,
+    };
+
     for url in &args.urls {
         let url = url.clone();
         let tx = tx.clone();
-        let validate = args.validate;
+        let validate = args.validate || args.revalidate;
+        let revalidate = args.revalidate;
+        let scan_options = Arc::clone(&scan_options);
+        let purge = purge.clone();
         tokio::spawn(async move {
-            let result: Result<serde_json::Value, Box<dyn Error + Send + Sync>> = if validate {
-                validate_cache(&url).await.map(|r| serde_json::to_value(r).unwrap()).map_err(|e| e.into())
+            let result: Result<serde_json::Value, ScanError> = if let Some((listen, windows)) = stale_check {
+                stale::stale_check(&url, &scan_options.client, &scan_options.cdn_registry, listen, windows)
+                    .await
+                    .map(|r| serde_json::to_value(r).unwrap())
+            } else if let Some((step, plan)) = purge {
+                let request_headers = scan_options.request_headers();
+                purge::purge_verify(&url, &scan_options.client, &request_headers, &scan_options.cdn_registry, scan_options.fan_out.as_ref(), &step, plan)
+                    .await
+                    .map(|r| serde_json::to_value(r).unwrap())
+            } else if validate {
+                validate_cache(&scan_options.client, &url, &scan_options.request_headers(), revalidate).await.map(|r| serde_json::to_value(r).unwrap())
             } else {
-                check_cache(&url, false).await.map(|r| serde_json::to_value(r).unwrap()).map_err(|e| e.into())
+                check_cache(&url, &scan_options).await.map(|r| serde_json::to_value(r).unwrap())
             };
 
             tx.send((url, result)).await.unwrap();
@@ -89,11 +403,27 @@ async fn main() {
                             has_no_cache = true;
                         }
                     }
+                    if let Some(passed) = obj.get("passed") {
+                        if passed == &serde_json::Value::Bool(false) {
+                            has_no_cache = true;
+                        }
+                    }
+                    if let Some(invalidated) = obj.get("invalidated") {
+                        if invalidated == &serde_json::Value::Bool(false) {
+                            has_no_cache = true;
+                        }
+                    }
+                    if let Some(honors) = obj.get("revalidation").and_then(|r| r.get("honors_validators")) {
+                        if honors == &serde_json::Value::Bool(false) {
+                            has_no_cache = true;
+                        }
+                    }
                 }
                 results.push(json_result);
             },
             Err(e) => {
                 eprintln!("Error scanning {}: {}", url, e);
+                results.push(serde_json::json!({ "url": url, "error": e }));
                 has_errors = true;
             }
         }
@@ -0,0 +1,64 @@
+use crate::cache_checker::{header_text, observed_verdict, COMPARED_HEADERS};
+use crate::cdn::CdnRegistry;
+use crate::verdict::CacheStatus;
+use reqwest::header::HeaderMap;
+use serde::Serialize;
+
+/// Response headers that must not depend on the request method (RFC 9110 §9.3.2)
+fn compared_headers() -> impl Iterator<Item = &'static str> {
+    COMPARED_HEADERS.into_iter().chain(["content-length"])
+}
+
+/// A header whose value differs between HEAD and GET
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HeaderDifference {
+    pub header: String,
+    pub head: Option<String>,
+    pub get: Option<String>,
+}
+
+/// HEAD and GET responses for the same URL, side by side
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MethodComparison {
+    pub head_status: u16,
+    pub get_status: u16,
+    pub head_cache_status: Option<CacheStatus>,
+    pub get_cache_status: Option<CacheStatus>,
+    pub differences: Vec<HeaderDifference>,
+    pub consistent: bool,
+}
+
+impl MethodComparison {
+    pub fn compare(
+        head_status: u16,
+        head: &HeaderMap,
+        get_status: u16,
+        get: &HeaderMap,
+        registry: &CdnRegistry,
+        request_has_authorization: bool,
+    ) -> Self {
+        let differences: Vec<HeaderDifference> = compared_headers()
+            // HEAD responses commonly omit Content-Length; only a conflicting value is a problem
+            .filter(|name| !(*name == "content-length" && header_text(head, name).is_none()))
+            .filter_map(|name| {
+                let (head, get) = (header_text(head, name), header_text(get, name));
+                (head != get).then(|| HeaderDifference { header: name.to_string(), head, get })
+            })
+            .collect();
+
+        let head_cache_status = observed_verdict(head, registry, request_has_authorization).map(|v| v.status);
+        let get_cache_status = observed_verdict(get, registry, request_has_authorization).map(|v| v.status);
+        let consistent = differences.is_empty()
+            && head_status == get_status
+            && head_cache_status.map(|s| s.is_cached()) == get_cache_status.map(|s| s.is_cached());
+
+        MethodComparison {
+            head_status,
+            get_status,
+            head_cache_status,
+            get_cache_status,
+            differences,
+            consistent,
+        }
+    }
+}
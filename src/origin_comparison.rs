@@ -0,0 +1,68 @@
+use crate::cache_checker::{header_text, observed_verdict, COMPARED_HEADERS};
+use crate::cdn::CdnRegistry;
+use crate::verdict::CacheStatus;
+use reqwest::header::HeaderMap;
+use serde::Serialize;
+use std::net::IpAddr;
+
+/// Headers compared between the edge response and the origin response
+fn compared_headers() -> impl Iterator<Item = &'static str> {
+    COMPARED_HEADERS.into_iter().chain(["set-cookie"])
+}
+
+/// A header whose value differs between edge and origin
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OriginDifference {
+    pub header: String,
+    pub edge: Option<String>,
+    pub origin: Option<String>,
+}
+
+/// The same URL fetched through the CDN and directly from an origin address
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OriginComparison {
+    pub origin_ip: IpAddr,
+    pub edge_status: u16,
+    pub origin_status: u16,
+    pub edge_cache_status: Option<CacheStatus>,
+    pub differences: Vec<OriginDifference>,
+    /// `Some(false)` when ETag or Last-Modified show the edge holds another version
+    pub same_object: Option<bool>,
+}
+
+impl OriginComparison {
+    pub fn compare(
+        origin_ip: IpAddr,
+        edge_status: u16,
+        edge: &HeaderMap,
+        origin_status: u16,
+        origin: &HeaderMap,
+        registry: &CdnRegistry,
+        request_has_authorization: bool,
+    ) -> Self {
+        let differences: Vec<OriginDifference> = compared_headers()
+            .filter_map(|name| {
+                let (edge, origin) = (header_text(edge, name), header_text(origin, name));
+                (edge != origin).then(|| OriginDifference { header: name.to_string(), edge, origin })
+            })
+            .collect();
+
+        // Only validators present on both sides say anything about the object version
+        let same_object = ["etag", "last-modified"]
+            .iter()
+            .filter_map(|name| match (header_text(edge, name), header_text(origin, name)) {
+                (Some(e), Some(o)) => Some(e == o),
+                _ => None,
+            })
+            .reduce(|a, b| a && b);
+
+        OriginComparison {
+            origin_ip,
+            edge_status,
+            origin_status,
+            edge_cache_status: observed_verdict(edge, registry, request_has_authorization).map(|v| v.status),
+            differences,
+            same_object,
+        }
+    }
+}
@@ -0,0 +1,308 @@
+use crate::cache_checker::observed_verdict;
+use crate::cdn::CdnRegistry;
+use crate::error::{ErrorKind, ScanError};
+use crate::fan_out::{self, FanOutTargets};
+use crate::http_client::SniperClient;
+use crate::utils::print_purge;
+use crate::verdict::CacheStatus;
+use reqwest::header::{HeaderMap, AGE, AUTHORIZATION, ETAG};
+use reqwest::{Method, Url};
+use serde::Serialize;
+use std::collections::hash_map::DefaultHasher;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::net::IpAddr;
+use std::time::{Duration, Instant};
+use tokio::process::Command;
+use tokio::task::JoinSet;
+
+/// Environment variable holding the URL for a purge command
+pub const PURGE_URL_ENV: &str = "CACHE_SNIPER_URL";
+
+/// How the object gets purged
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PurgeStep {
+    /// Run with `sh -c`; the URL is in `CACHE_SNIPER_URL`
+    Command(String),
+    /// Send `method` (e.g. `PURGE` or `BAN`) to `url`, or to the verified URL itself
+    Request { method: Method, url: Option<String> },
+}
+
+impl fmt::Display for PurgeStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PurgeStep::Command(command) => write!(f, "`{}`", command),
+            PurgeStep::Request { method, url: Some(url) } => write!(f, "{} {}", method, url),
+            PurgeStep::Request { method, url: None } => write!(f, "{} <url>", method),
+        }
+    }
+}
+
+/// Parses a purge method such as `purge` or `BAN`
+pub fn parse_method(s: &str) -> Result<Method, String> {
+    Method::from_bytes(s.trim().to_ascii_uppercase().as_bytes()).map_err(|_| format!("invalid HTTP method '{}'", s))
+}
+
+/// How often to poll after purging and for how long
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PollPlan {
+    pub interval: Duration,
+    pub deadline: Duration,
+}
+
+/// What identifies the cached object at one moment
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ObjectSnapshot {
+    pub http_status: u16,
+    pub etag: Option<String>,
+    /// Hex hash of the body; only compared within one run
+    pub body_hash: String,
+    pub age: Option<u64>,
+    pub cache_status: CacheStatus,
+}
+
+impl ObjectSnapshot {
+    pub fn capture(http_status: u16, headers: &HeaderMap, body: &str, registry: &CdnRegistry, request_has_authorization: bool) -> Self {
+        let mut hasher = DefaultHasher::new();
+        body.hash(&mut hasher);
+        ObjectSnapshot {
+            http_status,
+            etag: headers.get(ETAG).and_then(|v| v.to_str().ok()).map(str::to_string),
+            body_hash: format!("{:016x}", hasher.finish()),
+            age: headers.get(AGE).and_then(|v| v.to_str().ok()).and_then(|v| v.trim().parse().ok()),
+            cache_status: observed_verdict(headers, registry, request_has_authorization).map_or(CacheStatus::Unknown, |v| v.status),
+        }
+    }
+
+    /// Why `self` is a fresh object compared to `before`, if it is one. An
+    /// error page is no fresh object: only a 2xx with the status of `before` counts.
+    pub fn invalidated_since(&self, before: &ObjectSnapshot) -> Option<String> {
+        if !(200..300).contains(&self.http_status) || self.http_status != before.http_status {
+            return None;
+        }
+        if self.etag != before.etag {
+            return Some("ETag changed".to_string());
+        }
+        if self.body_hash != before.body_hash {
+            return Some("body changed".to_string());
+        }
+        if let (Some(was), Some(now)) = (before.age, self.age) {
+            // A copy that survived the purge can only have grown older
+            if now < was {
+                return Some(format!("Age dropped from {} to {}", was, now));
+            }
+        }
+        if before.cache_status.is_cached() && matches!(self.cache_status, CacheStatus::Miss | CacheStatus::Expired) {
+            return Some(format!("edge answered {}", self.cache_status));
+        }
+        None
+    }
+}
+
+/// Purge outcome as seen through one edge address (or the default route)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EdgeInvalidation {
+    /// `None` when the URL was polled without pinning an address
+    pub ip: Option<IpAddr>,
+    pub before: Option<ObjectSnapshot>,
+    /// Last response seen while polling
+    pub after: Option<ObjectSnapshot>,
+    pub polls: u32,
+    /// From the end of the purge step to the first fresh response
+    pub time_to_invalidate_ms: Option<f64>,
+    pub reason: Option<String>,
+    pub error: Option<String>,
+}
+
+impl EdgeInvalidation {
+    fn failed(ip: Option<IpAddr>, error: impl Into<String>) -> Self {
+        EdgeInvalidation {
+            ip,
+            before: None,
+            after: None,
+            polls: 0,
+            time_to_invalidate_ms: None,
+            reason: None,
+            error: Some(error.into()),
+        }
+    }
+}
+
+/// Per-edge results of a purge verification
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PurgeReport {
+    pub url: String,
+    pub purge: String,
+    /// Exit status of the command or HTTP status of the purge request
+    pub purge_outcome: String,
+    pub edges: Vec<EdgeInvalidation>,
+    /// Every edge served a fresh object before the deadline
+    pub invalidated: bool,
+    /// Slowest edge, when all of them were invalidated
+    pub time_to_invalidate_ms: Option<f64>,
+}
+
+impl PurgeReport {
+    pub fn new(url: &str, purge: &PurgeStep, purge_outcome: String, edges: Vec<EdgeInvalidation>) -> Self {
+        let times = edges.iter().map(|e| e.time_to_invalidate_ms).collect::<Option<Vec<_>>>().filter(|t| !t.is_empty());
+        PurgeReport {
+            url: url.to_string(),
+            purge: purge.to_string(),
+            purge_outcome,
+            invalidated: times.is_some(),
+            time_to_invalidate_ms: times.map(|t| t.into_iter().fold(0.0, f64::max)),
+            edges,
+        }
+    }
+}
+
+/// Runs the purge step for `url` and describes its outcome
+pub async fn run_purge(step: &PurgeStep, url: &str, client: &SniperClient, request_headers: &HeaderMap) -> Result<String, ScanError> {
+    match step {
+        PurgeStep::Command(command) => {
+            let output = Command::new("sh")
+                .arg("-c")
+                .arg(command)
+                .env(PURGE_URL_ENV, url)
+                .output()
+                .await
+                .map_err(|e| ScanError::new(ErrorKind::Other, format!("cannot run purge command: {}", e)))?;
+            if !output.status.success() {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                return Err(ScanError::new(ErrorKind::Other, format!("purge command failed ({}): {}", output.status, stderr.trim())));
+            }
+            Ok(output.status.to_string())
+        }
+        PurgeStep::Request { method, url: target } => {
+            let response = client.send(method.clone(), target.as_deref().unwrap_or(url), request_headers).await?;
+            if !response.status.is_success() {
+                return Err(ScanError::http_status(response.status.as_u16()));
+            }
+            Ok(format!("HTTP {}", response.status.as_u16()))
+        }
+    }
+}
+
+/// Clients to poll through: one per fan-out address, or the default client
+async fn edge_clients(
+    url: &str,
+    client: &SniperClient,
+    targets: Option<&FanOutTargets>,
+) -> Result<Vec<(Option<IpAddr>, Result<SniperClient, ScanError>)>, ScanError> {
+    let Some(targets) = targets else {
+        return Ok(vec![(None, Ok(client.clone()))]);
+    };
+    let host = Url::parse(url)
+        .ok()
+        .and_then(|u| u.host_str().map(str::to_string))
+        .ok_or_else(|| ScanError::new(ErrorKind::InvalidUrl, format!("no host in '{}'", url)))?;
+    let addrs = fan_out::target_addresses(url, targets).await?;
+    Ok(addrs.into_iter().map(|ip| (Some(ip), client.pinned(&host, &[ip]).map_err(ScanError::from))).collect())
+}
+
+/// Polls `url` until it differs from `before` or the deadline passes
+#[allow(clippy::too_many_arguments)]
+async fn poll_edge(
+    ip: Option<IpAddr>,
+    client: SniperClient,
+    url: String,
+    request_headers: HeaderMap,
+    registry: CdnRegistry,
+    before: ObjectSnapshot,
+    purged_at: Instant,
+    plan: PollPlan,
+) -> EdgeInvalidation {
+    let mut edge = EdgeInvalidation {
+        ip,
+        before: Some(before.clone()),
+        after: None,
+        polls: 0,
+        time_to_invalidate_ms: None,
+        reason: None,
+        error: None,
+    };
+    loop {
+        edge.polls += 1;
+        match client.fetch_body(&url, &request_headers).await {
+            Ok((fetched, body)) => {
+                let elapsed = purged_at.elapsed();
+                let snapshot = ObjectSnapshot::capture(
+                    fetched.status.as_u16(),
+                    &fetched.headers,
+                    &body,
+                    &registry,
+                    request_headers.contains_key(AUTHORIZATION),
+                );
+                edge.reason = snapshot.invalidated_since(&before);
+                edge.after = Some(snapshot);
+                edge.error = None;
+                if edge.reason.is_some() {
+                    edge.time_to_invalidate_ms = Some(elapsed.as_secs_f64() * 1000.0);
+                    return edge;
+                }
+            }
+            Err(e) => edge.error = Some(e.to_string()),
+        }
+        if purged_at.elapsed() + plan.interval > plan.deadline {
+            return edge;
+        }
+        tokio::time::sleep(plan.interval).await;
+    }
+}
+
+/// Captures the object on every edge, runs the purge step, then polls every
+/// edge in parallel until a fresh object appears or `plan.deadline` passes
+pub async fn purge_verify(
+    url: &str,
+    client: &SniperClient,
+    request_headers: &HeaderMap,
+    registry: &CdnRegistry,
+    targets: Option<&FanOutTargets>,
+    step: &PurgeStep,
+    plan: PollPlan,
+) -> Result<PurgeReport, ScanError> {
+    let has_authorization = request_headers.contains_key(AUTHORIZATION);
+    let mut edges = Vec::new();
+    for (ip, edge_client) in edge_clients(url, client, targets).await? {
+        let before = match &edge_client {
+            Ok(edge_client) => edge_client
+                .fetch_body(url, request_headers)
+                .await
+                .map(|(fetched, body)| ObjectSnapshot::capture(fetched.status.as_u16(), &fetched.headers, &body, registry, has_authorization)),
+            Err(e) => Err(e.clone()),
+        };
+        edges.push((ip, edge_client, before));
+    }
+    // Nothing to compare against: do not purge
+    if edges.iter().all(|(_, _, before)| before.is_err()) {
+        if let Some((_, _, Err(e))) = edges.into_iter().next() {
+            return Err(e);
+        }
+        return Err(ScanError::new(ErrorKind::Other, "No edges to verify"));
+    }
+
+    let purge_outcome = run_purge(step, url, client, request_headers).await?;
+    let purged_at = Instant::now();
+
+    let mut tasks = JoinSet::new();
+    let mut results = Vec::with_capacity(edges.len());
+    for (index, (ip, edge_client, before)) in edges.into_iter().enumerate() {
+        match (edge_client, before) {
+            (Ok(edge_client), Ok(before)) => {
+                let (url, request_headers, registry) = (url.to_string(), request_headers.clone(), registry.clone());
+                tasks.spawn(async move {
+                    (index, poll_edge(ip, edge_client, url, request_headers, registry, before, purged_at, plan).await)
+                });
+            }
+            (Err(e), _) | (_, Err(e)) => results.push((index, EdgeInvalidation::failed(ip, e.to_string()))),
+        }
+    }
+    while let Some(joined) = tasks.join_next().await {
+        results.push(joined.map_err(|e| ScanError::new(ErrorKind::Other, e.to_string()))?);
+    }
+    results.sort_by_key(|(index, _)| *index);
+
+    let report = PurgeReport::new(url, step, purge_outcome, results.into_iter().map(|(_, edge)| edge).collect());
+    print_purge(&report);
+    Ok(report)
+}
@@ -0,0 +1,251 @@
+use crate::cache_checker::observed_verdict;
+use crate::cdn::CdnRegistry;
+use crate::error::{ErrorKind, ScanError};
+use crate::http_client::{RequestMethod, SniperClient};
+use crate::verdict::CacheStatus;
+use reqwest::header::{HeaderMap, AGE, AUTHORIZATION, ETAG};
+use reqwest::Url;
+use serde::Serialize;
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Parameter names sent by the probe, so they are easy to spot in logs
+pub const BUST_PARAM: &str = "cache_sniper_bust";
+const ORDER_PARAMS: [&str; 2] = ["cache_sniper_a", "cache_sniper_b"];
+
+/// How a probe URL differs from the scanned URL
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryVariant {
+    /// An extra parameter with a unique value, like a cache buster
+    Random,
+    /// The same parameters in reverse order
+    Reordered,
+    /// `utm_*` marketing parameters
+    Utm,
+}
+
+impl fmt::Display for QueryVariant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            QueryVariant::Random => "random parameter",
+            QueryVariant::Reordered => "reordered parameters",
+            QueryVariant::Utm => "UTM parameters",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Whether the query string is part of the cache key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyFinding {
+    /// Every variant got its own cache entry
+    Yes,
+    /// Every variant was served the cached object
+    No,
+    /// Some variants shared the entry and others did not
+    Partial,
+    /// No variant gave a conclusive answer
+    Unknown,
+}
+
+impl fmt::Display for KeyFinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            KeyFinding::Yes => "yes",
+            KeyFinding::No => "no",
+            KeyFinding::Partial => "partial",
+            KeyFinding::Unknown => "unknown",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// The cache signals of one response
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CacheObservation {
+    /// Status reported by the cache, `UNKNOWN` when it reports none
+    pub status: CacheStatus,
+    pub age: Option<u64>,
+    pub etag: Option<String>,
+}
+
+impl CacheObservation {
+    pub fn from_headers(headers: &HeaderMap, registry: &CdnRegistry, request_has_authorization: bool) -> Self {
+        CacheObservation {
+            status: observed_verdict(headers, registry, request_has_authorization).map(|v| v.status).unwrap_or(CacheStatus::Unknown),
+            age: headers.get(AGE).and_then(|v| v.to_str().ok()).and_then(|v| v.trim().parse().ok()),
+            etag: headers.get(ETAG).and_then(|v| v.to_str().ok()).map(str::to_string),
+        }
+    }
+
+    /// True when the response evidently came out of a cache
+    fn served_from_cache(&self) -> bool {
+        self.status.is_cached() || self.age.is_some_and(|age| age > 0)
+    }
+
+    /// Whether `self` was served from the same cache entry as `reference`;
+    /// `None` when the signals do not tell
+    pub fn shares_entry_with(&self, reference: &CacheObservation) -> Option<bool> {
+        if !reference.served_from_cache() {
+            return None;
+        }
+        if let (Some(a), Some(b)) = (&reference.etag, &self.etag) {
+            if a != b {
+                return Some(false);
+            }
+        }
+        if matches!(self.status, CacheStatus::Miss | CacheStatus::Expired) {
+            return Some(false);
+        }
+        match (reference.age, self.age) {
+            // A separate entry is younger than the one it is compared with
+            (Some(was), Some(now)) if was > 0 => Some(now >= was),
+            (Some(was), None) if was > 0 => Some(false),
+            _ if self.status.is_cached() => Some(true),
+            _ => None,
+        }
+    }
+}
+
+/// One probe request and what it says about the cache key
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct QueryProbe {
+    pub variant: QueryVariant,
+    pub url: String,
+    /// Request the probe is compared with: the scanned URL, or for a URL
+    /// without two parameters to swap, a primer in the original order
+    pub reference_url: String,
+    pub observation: CacheObservation,
+    pub shares_entry: Option<bool>,
+}
+
+/// How query parameters affect the cache key
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct QueryKeyAnalysis {
+    pub baseline: CacheObservation,
+    pub probes: Vec<QueryProbe>,
+    pub query_in_cache_key: KeyFinding,
+    pub warnings: Vec<String>,
+}
+
+impl QueryKeyAnalysis {
+    pub fn summarize(baseline: CacheObservation, probes: Vec<QueryProbe>, mut warnings: Vec<String>) -> Self {
+        let answers = probes.iter().filter_map(|p| p.shares_entry).collect::<Vec<_>>();
+        let query_in_cache_key = if answers.is_empty() {
+            KeyFinding::Unknown
+        } else if answers.iter().all(|shared| !shared) {
+            KeyFinding::Yes
+        } else if answers.iter().all(|shared| *shared) {
+            KeyFinding::No
+        } else {
+            KeyFinding::Partial
+        };
+
+        if !baseline.served_from_cache() {
+            warnings.push("The scanned URL was not served from cache, so no variant can be compared with it".to_string());
+        }
+        for probe in &probes {
+            match (probe.variant, probe.shares_entry) {
+                (QueryVariant::Random, Some(true)) => {
+                    warnings.push("Unknown query parameters are ignored: cache busters will not bypass the cache".to_string())
+                }
+                (QueryVariant::Utm, Some(false)) => {
+                    warnings.push("UTM parameters create separate cache entries; strip them at the edge".to_string())
+                }
+                (QueryVariant::Reordered, Some(false)) => {
+                    warnings.push("Parameter order splits the cache; sort the query string at the edge".to_string())
+                }
+                _ => {}
+            }
+        }
+
+        QueryKeyAnalysis { baseline, probes, query_in_cache_key, warnings }
+    }
+}
+
+/// Probe URLs for `url` as `(variant, url, primer)`; a primer is requested
+/// first and used as the reference instead of `url`
+pub fn variant_urls(url: &str, nonce: &str) -> Result<Vec<(QueryVariant, String, Option<String>)>, ScanError> {
+    let base = Url::parse(url).map_err(|e| ScanError::new(ErrorKind::InvalidUrl, e.to_string()))?;
+    let pairs = base.query_pairs().into_owned().collect::<Vec<_>>();
+    let with_pairs = |extra: &[(&str, &str)], original: &[(String, String)]| {
+        let mut variant = base.clone();
+        variant.set_query(None);
+        {
+            let mut query = variant.query_pairs_mut();
+            for (name, value) in original {
+                query.append_pair(name, value);
+            }
+            for (name, value) in extra {
+                query.append_pair(name, value);
+            }
+        }
+        variant.to_string()
+    };
+
+    let mut variants = vec![(QueryVariant::Random, with_pairs(&[(BUST_PARAM, nonce)], &pairs), None)];
+
+    let mut reversed = pairs.clone();
+    reversed.reverse();
+    if reversed != pairs {
+        variants.push((QueryVariant::Reordered, with_pairs(&[], &reversed), None));
+    } else {
+        // Nothing to swap: add two parameters and compare both orders
+        let [a, b] = ORDER_PARAMS;
+        let primer = with_pairs(&[(a, nonce), (b, nonce)], &pairs);
+        variants.push((QueryVariant::Reordered, with_pairs(&[(b, nonce), (a, nonce)], &pairs), Some(primer)));
+    }
+
+    let utm = [("utm_source", "cache_sniper"), ("utm_medium", "probe"), ("utm_campaign", nonce)];
+    variants.push((QueryVariant::Utm, with_pairs(&utm, &pairs), None));
+    Ok(variants)
+}
+
+/// Requests `url` and each query variant and compares their cache signals
+pub async fn probe_query_key(
+    url: &str,
+    client: &SniperClient,
+    method: RequestMethod,
+    request_headers: &HeaderMap,
+    registry: &CdnRegistry,
+) -> Result<QueryKeyAnalysis, ScanError> {
+    let has_authorization = request_headers.contains_key(AUTHORIZATION);
+    let observe = |target: String| async move {
+        let fetched = client.fetch_headers(&target, method, request_headers).await?;
+        Ok::<_, ScanError>(CacheObservation::from_headers(&fetched.headers, registry, has_authorization))
+    };
+
+    let baseline = observe(url.to_string()).await?;
+    let nonce = format!("{:x}", SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or_default());
+
+    let mut probes = Vec::new();
+    let mut warnings = Vec::new();
+    for (variant, probe_url, primer) in variant_urls(url, &nonce)? {
+        let reference = match &primer {
+            // The first request fills the entry, the second shows it cached
+            Some(primer) => match observe(primer.clone()).await {
+                Ok(_) => observe(primer.clone()).await,
+                Err(e) => Err(e),
+            },
+            None => Ok(baseline.clone()),
+        };
+        let observation = match reference {
+            Ok(reference) => observe(probe_url.clone()).await.map(|o| (reference, o)),
+            Err(e) => Err(e),
+        };
+        match observation {
+            Ok((reference, observation)) => probes.push(QueryProbe {
+                variant,
+                shares_entry: observation.shares_entry_with(&reference),
+                reference_url: primer.unwrap_or_else(|| url.to_string()),
+                url: probe_url,
+                observation,
+            }),
+            Err(e) => warnings.push(format!("Query probe with {} failed: {}", variant, e)),
+        }
+    }
+
+    Ok(QueryKeyAnalysis::summarize(baseline, probes, warnings))
+}
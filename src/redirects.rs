@@ -0,0 +1,59 @@
+use crate::cache_checker::observed_verdict;
+use crate::cache_control::CacheDirectives;
+use crate::cacheability::{shared_cacheability_for_status, Cacheability};
+use crate::cdn::CdnRegistry;
+use crate::verdict::CacheStatus;
+use reqwest::header::{HeaderMap, LOCATION};
+use serde::Serialize;
+
+/// One response in a redirect chain
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RedirectHop {
+    pub url: String,
+    pub status: u16,
+    pub location: Option<String>,
+    pub cache_control: Option<String>,
+    pub age: Option<String>,
+    /// Status reported by the cache that served this hop, or `UNCACHEABLE`/`PRIVATE`
+    /// when no cache reports one and a shared cache may not store the hop
+    pub cache_status: Option<CacheStatus>,
+    pub cache_verdict: Option<String>,
+    pub shared_cache: Cacheability,
+    pub warnings: Vec<String>,
+}
+
+impl RedirectHop {
+    /// Analyzes one hop; warnings are only raised for redirect responses
+    pub fn analyze(url: &str, status: u16, headers: &HeaderMap, registry: &CdnRegistry, request_has_authorization: bool) -> Self {
+        let text = |name| headers.get(name).and_then(|v| v.to_str().ok()).map(str::to_string);
+        let directives = CacheDirectives::from_headers(headers, "cache-control").unwrap_or_default();
+        let shared_cache = shared_cacheability_for_status(&directives, headers, request_has_authorization, status);
+        let verdict = observed_verdict(headers, registry, request_has_authorization);
+        let location = text(LOCATION.as_str());
+
+        let mut warnings = Vec::new();
+        if is_redirect(status) {
+            if directives.no_store {
+                warnings.push(format!("{} redirect is no-store: every visit pays an extra round-trip to origin", status));
+            } else if !shared_cache.cacheable {
+                warnings.push(format!("{} redirect is not cacheable by shared caches: {}", status, shared_cache.reason));
+            }
+        }
+
+        RedirectHop {
+            url: url.to_string(),
+            status,
+            location,
+            cache_control: text("cache-control"),
+            age: text("age"),
+            cache_status: verdict.as_ref().map(|v| v.status),
+            cache_verdict: verdict.map(|v| v.reason),
+            shared_cache,
+            warnings,
+        }
+    }
+}
+
+fn is_redirect(status: u16) -> bool {
+    matches!(status, 301 | 302 | 303 | 307 | 308)
+}
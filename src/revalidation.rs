@@ -0,0 +1,111 @@
+use reqwest::header::{HeaderMap, ETAG, IF_NONE_MATCH};
+use serde::Serialize;
+
+/// Headers a 304 must carry when the 200 had them (RFC 9110 §15.4.5)
+pub const NOT_MODIFIED_HEADERS: [&str; 6] = ["cache-control", "content-location", "date", "etag", "expires", "vary"];
+
+/// Whether an entity tag is strong or weak (`W/` prefix)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EtagStrength {
+    Strong,
+    Weak,
+}
+
+impl EtagStrength {
+    pub fn of(etag: &str) -> Self {
+        if etag.trim_start().starts_with("W/") {
+            EtagStrength::Weak
+        } else {
+            EtagStrength::Strong
+        }
+    }
+}
+
+/// The same tag with the opposite strength: `"abc"` ↔ `W/"abc"`
+pub fn flip_strength(etag: &str) -> String {
+    let etag = etag.trim();
+    match etag.strip_prefix("W/") {
+        Some(strong) => strong.to_string(),
+        None => format!("W/{}", etag),
+    }
+}
+
+/// Weak comparison (RFC 9110 §8.8.3.2): the opaque tags are equal, whether or
+/// not either tag is weak
+pub fn weak_match(a: &str, b: &str) -> bool {
+    opaque_tag(a) == opaque_tag(b)
+}
+
+fn opaque_tag(etag: &str) -> &str {
+    let etag = etag.trim();
+    etag.strip_prefix("W/").unwrap_or(etag)
+}
+
+/// Outcome of one conditional request
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ConditionalCheck {
+    /// Request header that carried the validator, e.g. `If-None-Match`
+    pub header: String,
+    pub sent: String,
+    pub status: u16,
+    pub body_bytes: usize,
+    /// Headers of the full response that the 304 left out
+    pub missing_headers: Vec<String>,
+    /// A 304 that, for `If-None-Match`, names a tag weakly matching the one sent
+    pub passed: bool,
+}
+
+impl ConditionalCheck {
+    pub fn evaluate(header: &str, sent: &str, status: u16, body_bytes: usize, full: &HeaderMap, response: &HeaderMap) -> Self {
+        let missing_headers = if status == 304 { missing_not_modified_headers(full, response) } else { Vec::new() };
+        // A 304 naming another representation did not match what was sent
+        let same_tag = !header.eq_ignore_ascii_case(IF_NONE_MATCH.as_str())
+            || response.get(ETAG).and_then(|v| v.to_str().ok()).is_none_or(|etag| weak_match(etag, sent));
+        ConditionalCheck {
+            header: header.to_string(),
+            sent: sent.to_string(),
+            status,
+            body_bytes,
+            missing_headers,
+            passed: status == 304 && same_tag,
+        }
+    }
+}
+
+/// Headers from `NOT_MODIFIED_HEADERS` present in `full` but absent from `not_modified`
+pub fn missing_not_modified_headers(full: &HeaderMap, not_modified: &HeaderMap) -> Vec<String> {
+    NOT_MODIFIED_HEADERS
+        .iter()
+        .filter(|name| full.contains_key(**name) && !not_modified.contains_key(**name))
+        .map(|name| name.to_string())
+        .collect()
+}
+
+/// How the server answers requests carrying the captured validators
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RevalidationReport {
+    pub etag_strength: Option<EtagStrength>,
+    pub if_none_match: Option<ConditionalCheck>,
+    pub if_modified_since: Option<ConditionalCheck>,
+    /// `If-None-Match` with the opposite-strength tag; weak comparison
+    /// (RFC 9110 §13.1.2) still requires a 304
+    pub weak_comparison: Option<ConditionalCheck>,
+    /// Every check returned a 304 that kept the required headers
+    pub honors_validators: bool,
+}
+
+impl RevalidationReport {
+    pub fn new(
+        etag_strength: Option<EtagStrength>,
+        if_none_match: Option<ConditionalCheck>,
+        if_modified_since: Option<ConditionalCheck>,
+        weak_comparison: Option<ConditionalCheck>,
+    ) -> Self {
+        let checks = [&if_none_match, &if_modified_since, &weak_comparison];
+        let ran = checks.iter().any(|c| c.is_some());
+        let honors_validators = ran
+            && checks.iter().flat_map(|c| c.as_ref()).all(|c| c.passed && c.missing_headers.is_empty());
+        RevalidationReport { etag_strength, if_none_match, if_modified_since, weak_comparison, honors_validators }
+    }
+}
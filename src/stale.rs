@@ -0,0 +1,275 @@
+use crate::cache_checker::observed_verdict;
+use crate::cdn::CdnRegistry;
+use crate::error::{ErrorKind, ScanError};
+use crate::http_client::{RequestMethod, SniperClient};
+use crate::utils::print_stale;
+use crate::verdict::CacheStatus;
+use reqwest::header::{HeaderMap, AGE};
+use reqwest::Url;
+use serde::Serialize;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::time::{sleep_until, Instant};
+use warp::http::{Response, StatusCode};
+use warp::Filter;
+
+/// Response header carrying the content version served by the origin
+pub const VERSION_HEADER: &str = "x-origin-version";
+
+/// Windows the origin advertises, in seconds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct StaleWindows {
+    pub max_age: u64,
+    pub stale_while_revalidate: u64,
+    pub stale_if_error: u64,
+}
+
+impl StaleWindows {
+    /// Cache-Control for `path`: `/swr-*` only allows stale-while-revalidate and
+    /// `/sie-*` only stale-if-error, so each scenario tests one directive
+    pub fn cache_control(&self, path: &str) -> String {
+        let stale = if path.starts_with("/sie-") {
+            format!("stale-if-error={}", self.stale_if_error)
+        } else {
+            format!("stale-while-revalidate={}", self.stale_while_revalidate)
+        };
+        format!("public, max-age={}, {}", self.max_age, stale)
+    }
+
+    /// Windows shorter than two seconds leave no room to observe them
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_age == 0 || self.stale_while_revalidate < 2 || self.stale_if_error < 2 {
+            return Err("max-age must be at least 1s and the stale windows at least 2s".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Local origin whose content version and health the scenarios control
+#[derive(Debug, Clone)]
+pub struct ControlledOrigin {
+    pub addr: SocketAddr,
+    version: Arc<AtomicU32>,
+    failing: Arc<AtomicBool>,
+    requests: Arc<AtomicU32>,
+}
+
+impl ControlledOrigin {
+    /// Serves `version N` on every path, or a bare 503 while failing
+    pub fn start(listen: SocketAddr, windows: StaleWindows) -> Result<Self, ScanError> {
+        let (version, failing, requests) = (Arc::new(AtomicU32::new(0)), Arc::new(AtomicBool::new(false)), Arc::new(AtomicU32::new(0)));
+        let state = (version.clone(), failing.clone(), requests.clone());
+        let route = warp::path::full().map(move |path: warp::path::FullPath| {
+            let (version, failing, requests) = &state;
+            requests.fetch_add(1, Ordering::SeqCst);
+            if failing.load(Ordering::SeqCst) {
+                return Response::builder().status(StatusCode::SERVICE_UNAVAILABLE).body("origin down\n".to_string()).unwrap();
+            }
+            let version = version.load(Ordering::SeqCst);
+            Response::builder()
+                .header("cache-control", windows.cache_control(path.as_str()))
+                .header("etag", format!("\"v{}\"", version))
+                .header(VERSION_HEADER, version)
+                .body(format!("version {}\n", version))
+                .unwrap()
+        });
+        let (addr, server) = warp::serve(route)
+            .try_bind_ephemeral(listen)
+            .map_err(|e| ScanError::new(ErrorKind::Connect, format!("cannot listen on {}: {}", listen, e)))?;
+        tokio::spawn(server);
+        Ok(ControlledOrigin { addr, version, failing, requests })
+    }
+
+    /// Publishes a new version and returns it
+    pub fn bump(&self) -> u32 {
+        self.version.fetch_add(1, Ordering::SeqCst) + 1
+    }
+
+    pub fn set_failing(&self, failing: bool) {
+        self.failing.store(failing, Ordering::SeqCst);
+    }
+
+    /// Requests that reached the origin so far
+    pub fn requests(&self) -> u32 {
+        self.requests.load(Ordering::SeqCst)
+    }
+}
+
+/// What a step expects from the cache
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case", tag = "kind", content = "version")]
+pub enum Expectation {
+    /// A 200 carrying this origin version
+    Version(u32),
+    /// The origin's 5xx passed through
+    OriginError,
+}
+
+impl Expectation {
+    pub fn met_by(&self, http_status: u16, version: Option<u32>) -> bool {
+        match self {
+            Expectation::Version(expected) => http_status == 200 && version == Some(*expected),
+            Expectation::OriginError => http_status >= 500,
+        }
+    }
+}
+
+/// One request of a scenario
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StaleStep {
+    pub description: String,
+    /// Seconds since the scenario primed the cache
+    pub at_secs: f64,
+    pub expected: Expectation,
+    pub http_status: Option<u16>,
+    pub cache_status: Option<CacheStatus>,
+    pub age: Option<u64>,
+    pub version: Option<u32>,
+    /// Requests the origin received since the previous step, so a background
+    /// revalidation shows up on the step after the stale response
+    pub origin_requests: u32,
+    pub passed: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ScenarioResult {
+    pub name: String,
+    pub url: String,
+    pub steps: Vec<StaleStep>,
+    pub passed: bool,
+}
+
+/// Outcome of the stale-while-revalidate and stale-if-error scenarios
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StaleReport {
+    pub cache_url: String,
+    pub origin: SocketAddr,
+    pub windows: StaleWindows,
+    pub scenarios: Vec<ScenarioResult>,
+    pub passed: bool,
+}
+
+/// Sends scenario requests through the cache and records each outcome
+struct Scenario<'a> {
+    client: &'a SniperClient,
+    registry: &'a CdnRegistry,
+    origin: &'a ControlledOrigin,
+    url: String,
+    started: Instant,
+    /// Origin request count after the previous step
+    origin_seen: u32,
+    steps: Vec<StaleStep>,
+}
+
+impl Scenario<'_> {
+    async fn step(&mut self, description: &str, expected: Expectation) {
+        let at_secs = self.started.elapsed().as_secs_f64();
+        let fetched = self.client.fetch_headers(&self.url, RequestMethod::Get, &HeaderMap::new()).await;
+        let origin_seen = self.origin.requests();
+        let origin_requests = origin_seen - std::mem::replace(&mut self.origin_seen, origin_seen);
+        let step = match fetched {
+            Ok(fetched) => {
+                let header = |name: &str| fetched.headers.get(name).and_then(|v| v.to_str().ok()).map(str::trim);
+                let http_status = fetched.status.as_u16();
+                let version = header(VERSION_HEADER).and_then(|v| v.parse().ok());
+                StaleStep {
+                    description: description.to_string(),
+                    at_secs,
+                    expected,
+                    http_status: Some(http_status),
+                    // The stale check never sends credentials
+                    cache_status: observed_verdict(&fetched.headers, self.registry, false).map(|v| v.status),
+                    age: header(AGE.as_str()).and_then(|v| v.parse().ok()),
+                    version,
+                    origin_requests,
+                    passed: expected.met_by(http_status, version),
+                    error: None,
+                }
+            }
+            Err(e) => StaleStep {
+                description: description.to_string(),
+                at_secs,
+                expected,
+                http_status: e.status,
+                cache_status: None,
+                age: None,
+                version: None,
+                origin_requests,
+                passed: e.status.is_some_and(|status| expected.met_by(status, None)),
+                error: Some(e.to_string()),
+            },
+        };
+        self.steps.push(step);
+    }
+
+    async fn wait_until(&self, secs: u64) {
+        sleep_until(self.started + Duration::from_secs(secs)).await;
+    }
+
+    fn finish(self, name: &str) -> ScenarioResult {
+        let passed = self.steps.iter().all(|s| s.passed);
+        ScenarioResult { name: name.to_string(), url: self.url, steps: self.steps, passed }
+    }
+}
+
+/// Starts the origin on `listen` and runs both scenarios through `cache_url`,
+/// which must forward every path to that origin. The retry policy of
+/// `client` is ignored so that origin errors reach the scenario.
+pub async fn stale_check(
+    cache_url: &str,
+    client: &SniperClient,
+    registry: &CdnRegistry,
+    listen: SocketAddr,
+    windows: StaleWindows,
+) -> Result<StaleReport, ScanError> {
+    windows.validate().map_err(|e| ScanError::new(ErrorKind::Other, e))?;
+    let base = Url::parse(cache_url).map_err(|e| ScanError::new(ErrorKind::InvalidUrl, e.to_string()))?;
+    let origin = ControlledOrigin::start(listen, windows)?;
+    // Fresh paths per run so entries left by an earlier run do not interfere
+    let nonce = format!("{:x}", SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default());
+    let url = |scenario: &str| {
+        base.join(&format!("{}-{}", scenario, nonce))
+            .map(String::from)
+            .map_err(|e| ScanError::new(ErrorKind::InvalidUrl, e.to_string()))
+    };
+    let client = client.without_retries();
+    let StaleWindows { max_age, stale_while_revalidate: swr, stale_if_error: sie } = windows;
+
+    let mut scenario = Scenario { client: &client, registry, origin: &origin, url: url("swr")?, started: Instant::now(), origin_seen: origin.requests(), steps: vec![] };
+    let v1 = origin.bump();
+    scenario.step("first request fills the cache", Expectation::Version(v1)).await;
+    scenario.wait_until(max_age + 1).await;
+    let v2 = origin.bump();
+    scenario.step("expired, within stale-while-revalidate: stale copy served", Expectation::Version(v1)).await;
+    scenario.wait_until(max_age + 2).await;
+    scenario.step("background revalidation stored the new version", Expectation::Version(v2)).await;
+    scenario.wait_until(2 * max_age + swr + 3).await;
+    let v3 = origin.bump();
+    scenario.step("past stale-while-revalidate: revalidated before responding", Expectation::Version(v3)).await;
+    let swr_result = scenario.finish("stale-while-revalidate");
+
+    let mut scenario = Scenario { client: &client, registry, origin: &origin, url: url("sie")?, started: Instant::now(), origin_seen: origin.requests(), steps: vec![] };
+    let v4 = origin.bump();
+    scenario.step("first request fills the cache", Expectation::Version(v4)).await;
+    origin.set_failing(true);
+    scenario.wait_until(max_age + 1).await;
+    scenario.step("expired, origin failing, within stale-if-error: stale copy served", Expectation::Version(v4)).await;
+    scenario.wait_until(max_age + sie + 1).await;
+    scenario.step("past stale-if-error: origin error passed through", Expectation::OriginError).await;
+    origin.set_failing(false);
+    let sie_result = scenario.finish("stale-if-error");
+
+    let scenarios = vec![swr_result, sie_result];
+    let report = StaleReport {
+        cache_url: cache_url.to_string(),
+        origin: origin.addr,
+        windows,
+        passed: scenarios.iter().all(|s| s.passed),
+        scenarios,
+    };
+    print_stale(&report);
+    Ok(report)
+}
@@ -0,0 +1,109 @@
+use serde::Serialize;
+use std::net::{IpAddr, SocketAddr};
+use std::time::{Duration, Instant};
+use tokio::net::{lookup_host, TcpStream};
+use tokio::time::timeout;
+use tokio_native_tls::{native_tls, TlsConnector};
+
+/// Durations of the connection phases, measured on a separate probe connection.
+/// They describe how long a new connection takes, not the scan request's own.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ConnectionTimings {
+    /// `None` when the address was pinned with --resolve or --connect-to
+    pub dns_ms: Option<f64>,
+    pub connect_ms: Option<f64>,
+    /// `None` for plain HTTP
+    pub tls_ms: Option<f64>,
+}
+
+/// Where the time of a scan request went
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Timings {
+    /// Connection phases of the probe connection, not of the scan request;
+    /// they are not part of `ttfb_ms` and do not add up with it
+    pub probe_dns_ms: Option<f64>,
+    pub probe_connect_ms: Option<f64>,
+    pub probe_tls_ms: Option<f64>,
+    /// From sending the request to receiving the response headers, including
+    /// any connection setup of the scan request itself
+    pub ttfb_ms: f64,
+    /// From sending the request to receiving the last body byte
+    pub total_ms: f64,
+}
+
+impl Timings {
+    pub fn new(connection: ConnectionTimings, ttfb: Duration, total: Duration) -> Self {
+        Timings {
+            probe_dns_ms: connection.dns_ms,
+            probe_connect_ms: connection.connect_ms,
+            probe_tls_ms: connection.tls_ms,
+            ttfb_ms: millis(ttfb),
+            total_ms: millis(total),
+        }
+    }
+
+    /// One-line summary such as `TTFB 80 ms, total 96 ms (probe connection: DNS 2 ms, connect 11 ms, TLS 24 ms)`
+    pub fn summary(&self) -> String {
+        let phases = [("DNS", self.probe_dns_ms), ("connect", self.probe_connect_ms), ("TLS", self.probe_tls_ms)];
+        let probe = phases
+            .iter()
+            .filter_map(|(name, ms)| ms.map(|ms| format!("{} {:.0} ms", name, ms)))
+            .collect::<Vec<_>>();
+        let request = format!("TTFB {:.0} ms, total {:.0} ms", self.ttfb_ms, self.total_ms);
+        if probe.is_empty() {
+            request
+        } else {
+            format!("{} (probe connection: {})", request, probe.join(", "))
+        }
+    }
+}
+
+/// Times DNS, TCP connect and (for `https`) the TLS handshake for `host`,
+/// connecting to `connect_host` (the same host unless overridden).
+///
+/// An IP address as `connect_host` skips the DNS lookup. Certificates are not
+/// verified because only the handshake duration matters; phases that fail
+/// are left as `None`.
+pub async fn probe(host: &str, connect_host: &str, port: u16, https: bool, limit: Duration) -> ConnectionTimings {
+    let mut timings = ConnectionTimings::default();
+
+    let addr = match connect_host.parse::<IpAddr>() {
+        Ok(ip) => SocketAddr::new(ip, port),
+        Err(_) => {
+            let started = Instant::now();
+            let Ok(Ok(mut addrs)) = timeout(limit, lookup_host((connect_host, port))).await else {
+                return timings;
+            };
+            timings.dns_ms = Some(millis(started.elapsed()));
+            match addrs.next() {
+                Some(addr) => addr,
+                None => return timings,
+            }
+        }
+    };
+
+    let started = Instant::now();
+    let Ok(Ok(stream)) = timeout(limit, TcpStream::connect(addr)).await else {
+        return timings;
+    };
+    timings.connect_ms = Some(millis(started.elapsed()));
+
+    if https {
+        let connector = native_tls::TlsConnector::builder()
+            .danger_accept_invalid_certs(true)
+            .danger_accept_invalid_hostnames(true)
+            .build();
+        if let Ok(connector) = connector {
+            let started = Instant::now();
+            if let Ok(Ok(_)) = timeout(limit, TlsConnector::from(connector).connect(host, stream)).await {
+                timings.tls_ms = Some(millis(started.elapsed()));
+            }
+        }
+    }
+
+    timings
+}
+
+fn millis(duration: Duration) -> f64 {
+    (duration.as_secs_f64() * 1000.0 * 10.0).round() / 10.0
+}
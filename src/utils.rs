@@ -1,22 +1,178 @@
+use crate::cache_control::CacheDirectives;
+use crate::fan_out::FanOutReport;
+use crate::purge::PurgeReport;
+use crate::stale::{Expectation, StaleReport};
+use crate::timing::Timings;
+use crate::verdict::Verdict;
+use crate::warmup::WarmupReport;
 use comfy_table::{Table, Cell, Row};
 use colored::*;
 /// Prints caching results in a formatted table
-pub fn print_results(url: &str, cache_control: &str, etag: &str, last_modified: &str, expires: &str) {
+#[allow(clippy::too_many_arguments)]
+pub fn print_results(
+    url: &str,
+    cache_control: &str,
+    directives: &CacheDirectives,
+    etag: &str,
+    last_modified: &str,
+    expires: &str,
+    verdict: &Verdict,
+    timings: &Timings,
+) {
     let mut table = Table::new();
     table.add_row(Row::from(vec![Cell::new("🔍 Header"), Cell::new("📜 Value")]));
     table.add_row(Row::from(vec![Cell::new("🛠 Cache-Control"), Cell::new(cache_control)]));
     table.add_row(Row::from(vec![Cell::new("🔄 ETag"), Cell::new(etag)]));
     table.add_row(Row::from(vec![Cell::new("📆 Last-Modified"), Cell::new(last_modified)]));
     table.add_row(Row::from(vec![Cell::new("⏳ Expires"), Cell::new(expires)]));
+    table.add_row(Row::from(vec![Cell::new("📊 Verdict"), Cell::new(format!("{} (TTFB {:.0} ms)", verdict.status, timings.ttfb_ms))]));
+    table.add_row(Row::from(vec![Cell::new("⏱ Timing"), Cell::new(timings.summary())]));
 
     println!("
 🌍 Scanning: {}", url);
     println!("{}", table);
-    if cache_control == "None" && etag == "None" && last_modified == "None" && expires == "None" {
-        println!("\n🚨 {} This page is NOT being cached!", "Warning:".red().bold());
-    } else if cache_control.contains("no-cache") || cache_control.contains("no-store") || cache_control.contains("max-age=0") {
+    let no_cache_headers = cache_control == "None" && etag == "None" && last_modified == "None" && expires == "None";
+    let uncacheable = directives.no_store || directives.requires_revalidation() || directives.max_age == Some(0);
+    if no_cache_headers || uncacheable {
         println!("\n🚨 {} This page is NOT being cached!", "Warning:".red().bold());
     } else {
         println!("\n✅ {} This page is being cached!", "Success:".green().bold());
     }
 }
+
+/// Prints one row per scanned address followed by the consistency summary
+pub fn print_fan_out(report: &FanOutReport) {
+    let mut table = Table::new();
+    table.add_row(Row::from(vec![Cell::new("🌐 Address"), Cell::new("Status"), Cell::new("Cache"), Cell::new("Age"), Cell::new("POP")]));
+    for scan in &report.scans {
+        let or_dash = |v: Option<String>| v.unwrap_or_else(|| "-".to_string());
+        let cache = match &scan.error {
+            Some(error) => format!("ERROR: {}", error),
+            None => or_dash(scan.cache_status.map(|s| s.to_string())),
+        };
+        table.add_row(Row::from(vec![
+            Cell::new(scan.ip),
+            Cell::new(or_dash(scan.http_status.map(|s| s.to_string()))),
+            Cell::new(cache),
+            Cell::new(or_dash(scan.age.clone())),
+            Cell::new(or_dash(scan.pop.clone())),
+        ]));
+    }
+    println!("{}", table);
+
+    let counts = report
+        .status_counts
+        .iter()
+        .map(|(status, count)| format!("{} {}", count, status))
+        .collect::<Vec<_>>()
+        .join(", ");
+    if report.consistent {
+        println!("✅ {} All {} addresses agree ({})", "Success:".green().bold(), report.scans.len(), counts);
+    } else {
+        println!("🚨 {} Addresses disagree: {}", "Warning:".red().bold(), counts);
+    }
+}
+
+/// Prints one row per warm-up request followed by the convergence summary
+pub fn print_warmup(report: &WarmupReport) {
+    let mut table = Table::new();
+    table.add_row(Row::from(vec![Cell::new("🔁 Request"), Cell::new("Status"), Cell::new("Cache"), Cell::new("Age"), Cell::new("Latency")]));
+    for sample in &report.samples {
+        table.add_row(Row::from(vec![
+            Cell::new(sample.request),
+            Cell::new(sample.http_status),
+            Cell::new(sample.cache_status),
+            Cell::new(sample.age.map(|a| a.to_string()).unwrap_or_else(|| "-".to_string())),
+            Cell::new(format!("{:.0} ms", sample.latency_ms)),
+        ]));
+    }
+    println!("{}", table);
+
+    match report.requests_to_hit {
+        Some(request) => println!("✅ {} First HIT on request {}", "Success:".green().bold(), request),
+        None => println!("🚨 {} No HIT within {} requests", "Warning:".red().bold(), report.samples.len()),
+    }
+    if !report.age_monotonic {
+        println!("⚠️  Age went backwards between requests");
+    }
+    if report.evicted {
+        println!("⚠️  The object was evicted or refetched after it became a HIT");
+    }
+}
+
+/// Prints one row per edge with its time-to-invalidate
+pub fn print_purge(report: &PurgeReport) {
+    println!("\n🧹 Purge verification for: {}", report.url);
+    println!("🔨 Purge {} → {}", report.purge, report.purge_outcome);
+
+    let mut table = Table::new();
+    table.add_row(Row::from(vec![Cell::new("🌐 Edge"), Cell::new("Polls"), Cell::new("Invalidated after"), Cell::new("Evidence")]));
+    for edge in &report.edges {
+        let evidence = match (&edge.reason, &edge.error) {
+            (Some(reason), _) => reason.clone(),
+            (None, Some(error)) => format!("ERROR: {}", error),
+            (None, None) => match (&edge.before, &edge.after) {
+                (Some(before), Some(after)) if after.http_status != before.http_status => {
+                    format!("last poll answered {} (was {})", after.http_status, before.http_status)
+                }
+                _ => "still the old object".to_string(),
+            },
+        };
+        table.add_row(Row::from(vec![
+            Cell::new(edge.ip.map(|ip| ip.to_string()).unwrap_or_else(|| "default".to_string())),
+            Cell::new(edge.polls),
+            Cell::new(edge.time_to_invalidate_ms.map(|ms| format!("{:.0} ms", ms)).unwrap_or_else(|| "-".to_string())),
+            Cell::new(evidence),
+        ]));
+    }
+    println!("{}", table);
+
+    match report.time_to_invalidate_ms {
+        Some(ms) => println!("✅ {} Every edge served a fresh object within {:.0} ms", "Success:".green().bold(), ms),
+        None => println!("🚨 {} Not every edge served a fresh object before the deadline", "Warning:".red().bold()),
+    }
+}
+
+/// Prints every scenario step with what was expected and what was served
+pub fn print_stale(report: &StaleReport) {
+    println!("\n🕰️  Stale content scenarios for: {} (origin on {})", report.cache_url, report.origin);
+    let mut table = Table::new();
+    table.add_row(Row::from(vec![
+        Cell::new("🧪 Step"),
+        Cell::new("At"),
+        Cell::new("Expected"),
+        Cell::new("Status"),
+        Cell::new("Cache"),
+        Cell::new("Age"),
+        Cell::new("Version"),
+        Cell::new(""),
+    ]));
+    for scenario in &report.scenarios {
+        for step in &scenario.steps {
+            let or_dash = |v: Option<String>| v.unwrap_or_else(|| "-".to_string());
+            let expected = match step.expected {
+                Expectation::Version(version) => format!("v{}", version),
+                Expectation::OriginError => "5xx".to_string(),
+            };
+            table.add_row(Row::from(vec![
+                Cell::new(format!("{}: {}", scenario.name, step.description)),
+                Cell::new(format!("{:.1}s", step.at_secs)),
+                Cell::new(expected),
+                Cell::new(or_dash(step.http_status.map(|s| s.to_string()))),
+                Cell::new(or_dash(step.cache_status.map(|s| s.to_string()))),
+                Cell::new(or_dash(step.age.map(|a| a.to_string()))),
+                Cell::new(or_dash(step.version.map(|v| format!("v{}", v)))),
+                Cell::new(if step.passed { "✅" } else { "🚨" }),
+            ]));
+        }
+    }
+    println!("{}", table);
+
+    for scenario in &report.scenarios {
+        if scenario.passed {
+            println!("✅ {} {} works within the advertised window", "Success:".green().bold(), scenario.name);
+        } else {
+            println!("🚨 {} {} is not honored", "Warning:".red().bold(), scenario.name);
+        }
+    }
+}
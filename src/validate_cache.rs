@@ -1,5 +1,7 @@
-use reqwest::header::{CACHE_CONTROL, ETAG, LAST_MODIFIED, AGE};
-use std::error::Error;
+use crate::error::{ErrorKind, ScanError};
+use crate::http_client::SniperClient;
+use crate::revalidation::{flip_strength, ConditionalCheck, EtagStrength, RevalidationReport};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, AGE, CACHE_CONTROL, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
 use serde::Serialize;
 use colored::*;
 
@@ -12,16 +14,66 @@ pub struct CacheValidationResult {
     last_modified_after: String,
     age: String,
     is_consistent: bool,
+    revalidation: Option<RevalidationReport>, // Conditional request checks, when requested
 }
 
-/// Validates cache consistency by comparing responses before and after `Cache-Control: no-cache`
-pub async fn validate_cache(url: &str) -> Result<CacheValidationResult, Box<dyn Error + Send + Sync>> {
-    let client = reqwest::Client::new();
+/// Sends one conditional GET carrying `value` in `header`
+async fn conditional_check(
+    client: &SniperClient,
+    url: &str,
+    request_headers: &HeaderMap,
+    full: &HeaderMap,
+    header: HeaderName,
+    value: &str,
+) -> Result<ConditionalCheck, ScanError> {
+    let mut conditional_headers = request_headers.clone();
+    conditional_headers.insert(header.clone(), HeaderValue::from_str(value)?);
+    let (response, body) = client.fetch_body(url, &conditional_headers).await?;
+    Ok(ConditionalCheck::evaluate(header.as_str(), value, response.status.as_u16(), body.len(), full, &response.headers))
+}
+
+/// Replays the captured validators and checks for 304 responses
+async fn revalidation_report(
+    client: &SniperClient,
+    url: &str,
+    request_headers: &HeaderMap,
+    full: &HeaderMap,
+) -> Result<RevalidationReport, ScanError> {
+    let etag = full.get(ETAG).and_then(|v| v.to_str().ok());
+    let last_modified = full.get(LAST_MODIFIED).and_then(|v| v.to_str().ok());
+
+    let mut if_none_match = None;
+    let mut weak_comparison = None;
+    if let Some(etag) = etag {
+        if_none_match = Some(conditional_check(client, url, request_headers, full, IF_NONE_MATCH, etag).await?);
+        weak_comparison = Some(conditional_check(client, url, request_headers, full, IF_NONE_MATCH, &flip_strength(etag)).await?);
+    }
+    let if_modified_since = match last_modified {
+        Some(date) => Some(conditional_check(client, url, request_headers, full, IF_MODIFIED_SINCE, date).await?),
+        None => None,
+    };
+
+    Ok(RevalidationReport::new(etag.map(EtagStrength::of), if_none_match, if_modified_since, weak_comparison))
+}
 
+fn print_check(check: &ConditionalCheck) {
+    let outcome = if check.passed { "✅" } else { "🚨" };
+    println!("{} {}: {} → {} ({} body bytes)", outcome, check.header, check.sent, check.status, check.body_bytes);
+    if !check.missing_headers.is_empty() {
+        println!("   ⚠️  304 is missing: {}", check.missing_headers.join(", "));
+    }
+}
+
+/// Validates cache consistency by comparing responses before and after `Cache-Control: no-cache`
+pub async fn validate_cache(
+    client: &SniperClient,
+    url: &str,
+    request_headers: &HeaderMap,
+    revalidate: bool,
+) -> Result<CacheValidationResult, ScanError> {
     // First Request (Normal request)
-    let res1 = client.get(url).send().await?;
-    let headers1 = res1.headers().clone();
-    let body1 = res1.text().await?;
+    let (res1, body1) = client.fetch_body(url, request_headers).await?;
+    let headers1 = res1.headers;
 
     // Extract headers
     let cache_control = headers1.get(CACHE_CONTROL).map(|v| v.to_str().unwrap_or("")).unwrap_or("");
@@ -32,16 +84,14 @@ pub async fn validate_cache(url: &str) -> Result<CacheValidationResult, Box<dyn
     // 🚨 If no cache-related headers exist, skip validation
     if cache_control.is_empty() && etag_before.is_empty() && last_modified_before.is_empty() {
         println!("\n🚨 {} No caching detected on {}, skipping validation!", "Warning:".red().bold(), url);
-        return Err("No caching detected".into());
+        return Err(ScanError::new(ErrorKind::Other, "No caching detected"));
     }
 
     // Second Request (Force refresh with `Cache-Control: no-cache`)
-    let res2 = client.get(url)
-        .header(CACHE_CONTROL, "no-cache")
-        .send().await?;
-
-    let headers2 = res2.headers().clone();
-    let body2 = res2.text().await?;
+    let mut refresh_headers = request_headers.clone();
+    refresh_headers.insert(CACHE_CONTROL, HeaderValue::from_static("no-cache"));
+    let (res2, body2) = client.fetch_body(url, &refresh_headers).await?;
+    let headers2 = res2.headers;
 
     let etag_after = headers2.get(ETAG).map(|v| v.to_str().unwrap_or("")).unwrap_or("").to_string();
     let last_modified_after = headers2.get(LAST_MODIFIED).map(|v| v.to_str().unwrap_or("")).unwrap_or("").to_string();
@@ -63,6 +113,25 @@ pub async fn validate_cache(url: &str) -> Result<CacheValidationResult, Box<dyn
         println!("🚨 {} Cache behavior is inconsistent!", "Warning:".red().bold());
     }
 
+    let revalidation = if revalidate {
+        let report = revalidation_report(client, url, request_headers, &headers1).await?;
+        println!("\n🔁 Revalidation:");
+        if let Some(strength) = report.etag_strength {
+            println!("🏷️  ETag is {}", if strength == EtagStrength::Weak { "weak" } else { "strong" });
+        }
+        for check in [&report.if_none_match, &report.weak_comparison, &report.if_modified_since].into_iter().flatten() {
+            print_check(check);
+        }
+        if report.honors_validators {
+            println!("✅ {} Server honors conditional requests", "Success:".green().bold());
+        } else {
+            println!("🚨 {} Server does not answer conditional requests with a proper 304", "Warning:".red().bold());
+        }
+        Some(report)
+    } else {
+        None
+    };
+
     Ok(CacheValidationResult {
         url: url.to_string(),
         etag_before: etag_before.to_string(),
@@ -71,5 +140,6 @@ pub async fn validate_cache(url: &str) -> Result<CacheValidationResult, Box<dyn
         last_modified_after,
         age: age_header.to_string(),
         is_consistent,
+        revalidation,
     })
 }
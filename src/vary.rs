@@ -0,0 +1,104 @@
+use crate::verdict::CacheStatus;
+use reqwest::header::HeaderMap;
+use serde::Serialize;
+
+/// Request fields whose values differ per client, so varying on them splits
+/// the cache into (nearly) one entry per user
+pub const HIGH_CARDINALITY_FIELDS: [&str; 4] = ["user-agent", "cookie", "authorization", "*"];
+
+/// Outcome of re-requesting with a different value for one Vary field
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct VaryProbe {
+    pub header: String,
+    pub sent_value: String,
+    pub status: CacheStatus,
+    /// `Some(true)` when the changed value produced a separate cache entry,
+    /// `None` when the result does not tell either way
+    pub keyed: Option<bool>,
+}
+
+/// What the `Vary` header does to the cache key
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct VaryAnalysis {
+    /// Lowercased field names, in header order
+    pub fields: Vec<String>,
+    /// Fields from `HIGH_CARDINALITY_FIELDS` that are listed
+    pub high_cardinality: Vec<String>,
+    pub warnings: Vec<String>,
+    /// Status of the unmodified request the probes are compared against
+    pub baseline_status: Option<CacheStatus>,
+    pub probes: Vec<VaryProbe>,
+}
+
+impl VaryAnalysis {
+    /// Analyzes every `Vary` line in `headers`; `None` when there is none
+    pub fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let values: Vec<&str> = headers
+            .get_all("vary")
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .collect();
+
+        if values.is_empty() {
+            return None;
+        }
+        Some(Self::parse(&values.join(", ")))
+    }
+
+    /// Parses a `Vary` field value, dropping duplicates
+    pub fn parse(value: &str) -> Self {
+        let mut fields: Vec<String> = Vec::new();
+        for field in value.split(',').map(|f| f.trim().to_ascii_lowercase()) {
+            if !field.is_empty() && !fields.contains(&field) {
+                fields.push(field);
+            }
+        }
+
+        let high_cardinality: Vec<String> = fields
+            .iter()
+            .filter(|f| HIGH_CARDINALITY_FIELDS.contains(&f.as_str()))
+            .cloned()
+            .collect();
+        let warnings = high_cardinality.iter().map(|f| warning(f)).collect();
+
+        VaryAnalysis { fields, high_cardinality, warnings, ..VaryAnalysis::default() }
+    }
+
+    /// Fields worth probing: `*` cannot be sent as a request header
+    pub fn probe_fields(&self) -> impl Iterator<Item = &str> {
+        self.fields.iter().map(String::as_str).filter(|f| *f != "*")
+    }
+}
+
+fn warning(field: &str) -> String {
+    match field {
+        "*" => "Vary: * makes every request unique; shared caches cannot reuse the response".to_string(),
+        "user-agent" => "Vary: User-Agent stores one copy per browser build; normalize it at the edge or vary on a device class".to_string(),
+        "cookie" => "Vary: Cookie stores one copy per visitor session; strip unneeded cookies or vary on a derived header".to_string(),
+        "authorization" => "Vary: Authorization stores one copy per credential; the hit ratio will be close to zero".to_string(),
+        other => format!("Vary: {} has high cardinality", other),
+    }
+}
+
+/// Value sent for `field` during a probe, unique per `nonce`
+pub fn probe_value(field: &str, nonce: &str) -> String {
+    match field {
+        "cookie" => format!("cache_sniper_vary_probe={}", nonce),
+        "authorization" => format!("Bearer cache-sniper-vary-probe-{}", nonce),
+        "user-agent" => format!("cache-sniper-vary-probe/{}", nonce),
+        _ => format!("cache-sniper-vary-probe-{}", nonce),
+    }
+}
+
+/// Interprets a probe against a baseline that was served from cache: a hit
+/// means the field is not part of the key, a miss means a new variant was created
+pub fn keyed(baseline: CacheStatus, probe: CacheStatus) -> Option<bool> {
+    if !baseline.is_cached() {
+        return None;
+    }
+    match probe {
+        CacheStatus::Hit | CacheStatus::Stale | CacheStatus::Revalidated | CacheStatus::Updating => Some(false),
+        CacheStatus::Miss | CacheStatus::Expired => Some(true),
+        _ => None,
+    }
+}
@@ -0,0 +1,86 @@
+use serde::Serialize;
+use std::fmt;
+
+/// Normalized cache status, stable across CDNs and serialized as e.g. `"HIT"`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum CacheStatus {
+    Hit,
+    Miss,
+    Expired,
+    Stale,
+    Bypass,
+    Revalidated,
+    Updating,
+    Dynamic,
+    Cacheable,
+    /// Cacheable by the browser only, not by shared caches
+    Private,
+    Uncacheable,
+    Unknown,
+}
+
+impl CacheStatus {
+    /// Maps a vendor status keyword (case-insensitive) to a status
+    pub fn from_keyword(keyword: &str) -> Option<Self> {
+        let status = match keyword.trim().to_ascii_lowercase().as_str() {
+            "hit" => CacheStatus::Hit,
+            "miss" => CacheStatus::Miss,
+            "expired" => CacheStatus::Expired,
+            "stale" => CacheStatus::Stale,
+            "bypass" => CacheStatus::Bypass,
+            "revalidated" => CacheStatus::Revalidated,
+            "updating" => CacheStatus::Updating,
+            "dynamic" => CacheStatus::Dynamic,
+            _ => return None,
+        };
+        Some(status)
+    }
+
+    /// True when the response was (or may be) served from a cache
+    pub fn is_cached(self) -> bool {
+        matches!(
+            self,
+            CacheStatus::Hit | CacheStatus::Stale | CacheStatus::Revalidated | CacheStatus::Updating | CacheStatus::Cacheable
+        )
+    }
+}
+
+impl fmt::Display for CacheStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            CacheStatus::Hit => "HIT",
+            CacheStatus::Miss => "MISS",
+            CacheStatus::Expired => "EXPIRED",
+            CacheStatus::Stale => "STALE",
+            CacheStatus::Bypass => "BYPASS",
+            CacheStatus::Revalidated => "REVALIDATED",
+            CacheStatus::Updating => "UPDATING",
+            CacheStatus::Dynamic => "DYNAMIC",
+            CacheStatus::Cacheable => "CACHEABLE",
+            CacheStatus::Private => "PRIVATE",
+            CacheStatus::Uncacheable => "UNCACHEABLE",
+            CacheStatus::Unknown => "UNKNOWN",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A cache status together with a human explanation
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Verdict {
+    pub status: CacheStatus,
+    pub reason: String,
+}
+
+impl Verdict {
+    pub fn new(status: CacheStatus, reason: impl Into<String>) -> Self {
+        Verdict { status, reason: reason.into() }
+    }
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} - {}", self.status, self.reason)
+    }
+}
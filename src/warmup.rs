@@ -0,0 +1,100 @@
+use crate::cache_checker::observed_verdict;
+use crate::cdn::CdnRegistry;
+use crate::error::ScanError;
+use crate::http_client::{RequestMethod, SniperClient};
+use crate::verdict::CacheStatus;
+use reqwest::header::{HeaderMap, AGE, AUTHORIZATION};
+use serde::Serialize;
+use std::time::Duration;
+
+/// How many sequential requests a warm-up probe sends and how far apart
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WarmupPlan {
+    pub requests: u32,
+    pub delay: Duration,
+}
+
+/// One request of a warm-up probe
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WarmupSample {
+    /// 1-based position in the probe
+    pub request: u32,
+    pub http_status: u16,
+    pub cache_status: CacheStatus,
+    pub age: Option<u64>,
+    pub latency_ms: f64,
+}
+
+impl WarmupSample {
+    pub fn from_response(
+        request: u32,
+        http_status: u16,
+        headers: &HeaderMap,
+        registry: &CdnRegistry,
+        request_has_authorization: bool,
+        latency: Duration,
+    ) -> Self {
+        WarmupSample {
+            request,
+            http_status,
+            cache_status: observed_verdict(headers, registry, request_has_authorization).map_or(CacheStatus::Unknown, |v| v.status),
+            age: headers.get(AGE).and_then(|v| v.to_str().ok()).and_then(|v| v.trim().parse().ok()),
+            latency_ms: latency.as_secs_f64() * 1000.0,
+        }
+    }
+}
+
+/// Samples of a warm-up probe and how the cache converged
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WarmupReport {
+    pub samples: Vec<WarmupSample>,
+    /// Number of the first request answered with a HIT
+    pub requests_to_hit: Option<u32>,
+    /// Age never went down between requests that carried it
+    pub age_monotonic: bool,
+    /// After the first HIT, a request was answered uncached or with a reset Age
+    pub evicted: bool,
+}
+
+impl WarmupReport {
+    pub fn summarize(samples: Vec<WarmupSample>) -> Self {
+        let first_hit = samples.iter().position(|s| s.cache_status == CacheStatus::Hit);
+        let ages = samples.iter().filter_map(|s| s.age).collect::<Vec<_>>();
+        let age_monotonic = ages.windows(2).all(|w| w[0] <= w[1]);
+
+        let evicted = first_hit.is_some_and(|first| {
+            let after_hit = &samples[first..];
+            let age_reset = after_hit.windows(2).any(|w| matches!((w[0].age, w[1].age), (Some(a), Some(b)) if b < a));
+            age_reset || after_hit.iter().any(|s| !s.cache_status.is_cached())
+        });
+
+        WarmupReport {
+            requests_to_hit: first_hit.map(|i| samples[i].request),
+            age_monotonic,
+            evicted,
+            samples,
+        }
+    }
+}
+
+/// Requests `url` `plan.requests` times in sequence, sleeping `plan.delay` in between
+pub async fn warm_up(
+    url: &str,
+    client: &SniperClient,
+    method: RequestMethod,
+    request_headers: &HeaderMap,
+    registry: &CdnRegistry,
+    plan: WarmupPlan,
+) -> Result<WarmupReport, ScanError> {
+    let has_authorization = request_headers.contains_key(AUTHORIZATION);
+    let mut samples = Vec::with_capacity(plan.requests as usize);
+    for request in 1..=plan.requests {
+        if request > 1 {
+            tokio::time::sleep(plan.delay).await;
+        }
+        let fetched = client.fetch_timed(url, method, request_headers).await?;
+        let latency = fetched.completed_time.duration_since(fetched.request_time).unwrap_or_default();
+        samples.push(WarmupSample::from_response(request, fetched.status.as_u16(), &fetched.headers, registry, has_authorization, latency));
+    }
+    Ok(WarmupReport::summarize(samples))
+}
@@ -1,4 +1,19 @@
-use cache_sniper::cache_checker::CacheResult;
+#![allow(clippy::bool_assert_comparison, clippy::single_component_path_imports)]
+
+mod common;
+
+use cache_sniper::cache_checker::{check_cache, response_verdict, CacheResult, ScanOptions};
+use cache_sniper::cdn::CdnRegistry;
+use cache_sniper::fan_out::FanOutTargets;
+use cache_sniper::cache_control::CacheDirectives;
+use cache_sniper::cacheability::Cacheability;
+use cache_sniper::freshness::FreshnessSource;
+use cache_sniper::http_client::{RequestMethod, RetryPolicy, SniperClient};
+use cache_sniper::timing::Timings;
+use cache_sniper::verdict::CacheStatus;
+use common::headers;
+use std::collections::BTreeMap;
+use warp::Filter;
 
 #[cfg(test)]
 mod cdn_detection_tests {
@@ -9,23 +24,54 @@ mod cdn_detection_tests {
         // Test that Cloudflare HIT status is correctly identified
         let result = CacheResult {
             url: "https://example.com".to_string(),
+            request_method: RequestMethod::Get,
+            redirect_chain: Vec::new(),
             cache_control: "max-age=3600".to_string(),
+            directives: CacheDirectives::parse("max-age=3600"),
+            targeted_cache_control: Vec::new(),
+            edge_ttl_header: "cache-control".to_string(),
+            surrogate_keys: Vec::new(),
             etag: "\"abc123\"".to_string(),
             last_modified: "None".to_string(),
             expires: "None".to_string(),
             cdn_provider: "cloudflare".to_string(),
+            cdn_details: BTreeMap::new(),
+            cdn_request_id: Some("12345".to_string()),
+            edge_location: None,
+            cache_tiers: Vec::new(),
+            cache_status_layers: Vec::new(),
             cf_cache_status: Some("HIT".to_string()),
             cf_ray: Some("12345".to_string()),
             x_cache: None,
             x_served_by: None,
             age: Some("120".to_string()),
             x_cache_hits: None,
+            x_check_cacheable: None,
+            x_cache_key: None,
+            x_true_cache_key: None,
+            freshness_lifetime_secs: Some(3600),
+            freshness_source: Some(FreshnessSource::MaxAge),
+            current_age_secs: 120,
+            ttl_remaining_secs: Some(3480),
+            is_fresh: true,
+            browser_cache: Cacheability { cacheable: true, reason: "fresh for 3600s (max-age)".to_string() },
+            shared_cache: Cacheability { cacheable: true, reason: "fresh for 3600s (max-age)".to_string() },
+            vary_analysis: None,
+            query_key: None,
+            method_comparison: None,
+            credential_impact: None,
+            origin_comparison: None,
+            fan_out: None,
+            warmup: None,
+            probe_warnings: Vec::new(),
             is_cached: true,
-            cache_verdict: "HIT - Served from Cloudflare cache".to_string(),
+            cache_status: CacheStatus::Hit,
+            cache_verdict: "Served from Cloudflare cache".to_string(),
+            timings: Timings::default(),
         };
 
         assert_eq!(result.is_cached, true);
-        assert!(result.cache_verdict.contains("HIT"));
+        assert_eq!(result.cache_status, CacheStatus::Hit);
         assert_eq!(result.cdn_provider, "cloudflare");
     }
 
@@ -33,69 +79,162 @@ mod cdn_detection_tests {
     fn test_cloudflare_cache_miss_verdict() {
         let result = CacheResult {
             url: "https://example.com".to_string(),
+            request_method: RequestMethod::Get,
+            redirect_chain: Vec::new(),
             cache_control: "max-age=3600".to_string(),
+            directives: CacheDirectives::parse("max-age=3600"),
+            targeted_cache_control: Vec::new(),
+            edge_ttl_header: "cache-control".to_string(),
+            surrogate_keys: Vec::new(),
             etag: "\"abc123\"".to_string(),
             last_modified: "None".to_string(),
             expires: "None".to_string(),
             cdn_provider: "cloudflare".to_string(),
+            cdn_details: BTreeMap::new(),
+            cdn_request_id: Some("12345".to_string()),
+            edge_location: None,
+            cache_tiers: Vec::new(),
+            cache_status_layers: Vec::new(),
             cf_cache_status: Some("MISS".to_string()),
             cf_ray: Some("12345".to_string()),
             x_cache: None,
             x_served_by: None,
             age: None,
             x_cache_hits: None,
+            x_check_cacheable: None,
+            x_cache_key: None,
+            x_true_cache_key: None,
+            freshness_lifetime_secs: Some(3600),
+            freshness_source: Some(FreshnessSource::MaxAge),
+            current_age_secs: 0,
+            ttl_remaining_secs: Some(3600),
+            is_fresh: true,
+            browser_cache: Cacheability { cacheable: true, reason: "fresh for 3600s (max-age)".to_string() },
+            shared_cache: Cacheability { cacheable: true, reason: "fresh for 3600s (max-age)".to_string() },
+            vary_analysis: None,
+            query_key: None,
+            method_comparison: None,
+            credential_impact: None,
+            origin_comparison: None,
+            fan_out: None,
+            warmup: None,
+            probe_warnings: Vec::new(),
             is_cached: false,
-            cache_verdict: "MISS - Origin fetch, not cached yet".to_string(),
+            cache_status: CacheStatus::Miss,
+            cache_verdict: "Origin fetch, not cached yet".to_string(),
+            timings: Timings::default(),
         };
 
         assert_eq!(result.is_cached, false);
-        assert!(result.cache_verdict.contains("MISS"));
+        assert_eq!(result.cache_status, CacheStatus::Miss);
     }
 
     #[test]
     fn test_cloudflare_dynamic_verdict() {
         let result = CacheResult {
             url: "https://example.com".to_string(),
+            request_method: RequestMethod::Get,
+            redirect_chain: Vec::new(),
             cache_control: "no-cache".to_string(),
+            directives: CacheDirectives::parse("no-cache"),
+            targeted_cache_control: Vec::new(),
+            edge_ttl_header: "cache-control".to_string(),
+            surrogate_keys: Vec::new(),
             etag: "None".to_string(),
             last_modified: "None".to_string(),
             expires: "None".to_string(),
             cdn_provider: "cloudflare".to_string(),
+            cdn_details: BTreeMap::new(),
+            cdn_request_id: Some("12345".to_string()),
+            edge_location: None,
+            cache_tiers: Vec::new(),
+            cache_status_layers: Vec::new(),
             cf_cache_status: Some("DYNAMIC".to_string()),
             cf_ray: Some("12345".to_string()),
             x_cache: None,
             x_served_by: None,
             age: None,
             x_cache_hits: None,
+            x_check_cacheable: None,
+            x_cache_key: None,
+            x_true_cache_key: None,
+            freshness_lifetime_secs: None,
+            freshness_source: None,
+            current_age_secs: 0,
+            ttl_remaining_secs: None,
+            is_fresh: false,
+            browser_cache: Cacheability { cacheable: false, reason: "no-cache requires revalidation on every use".to_string() },
+            shared_cache: Cacheability { cacheable: false, reason: "no-cache requires revalidation on every use".to_string() },
+            vary_analysis: None,
+            query_key: None,
+            method_comparison: None,
+            credential_impact: None,
+            origin_comparison: None,
+            fan_out: None,
+            warmup: None,
+            probe_warnings: Vec::new(),
             is_cached: false,
-            cache_verdict: "DYNAMIC - Content marked as uncacheable".to_string(),
+            cache_status: CacheStatus::Dynamic,
+            cache_verdict: "Content marked as uncacheable".to_string(),
+            timings: Timings::default(),
         };
 
         assert_eq!(result.is_cached, false);
-        assert!(result.cache_verdict.contains("DYNAMIC"));
+        assert_eq!(result.cache_status, CacheStatus::Dynamic);
     }
 
     #[test]
     fn test_fastly_cache_hit() {
         let result = CacheResult {
             url: "https://example.com".to_string(),
+            request_method: RequestMethod::Get,
+            redirect_chain: Vec::new(),
             cache_control: "max-age=3600".to_string(),
+            directives: CacheDirectives::parse("max-age=3600"),
+            targeted_cache_control: Vec::new(),
+            edge_ttl_header: "cache-control".to_string(),
+            surrogate_keys: Vec::new(),
             etag: "\"xyz789\"".to_string(),
             last_modified: "None".to_string(),
             expires: "None".to_string(),
             cdn_provider: "fastly".to_string(),
+            cdn_details: BTreeMap::new(),
+            cdn_request_id: None,
+            edge_location: None,
+            cache_tiers: Vec::new(),
+            cache_status_layers: Vec::new(),
             cf_cache_status: None,
             cf_ray: None,
             x_cache: Some("HIT".to_string()),
             x_served_by: Some("cache-lax123".to_string()),
             age: Some("45".to_string()),
             x_cache_hits: Some("3".to_string()),
+            x_check_cacheable: None,
+            x_cache_key: None,
+            x_true_cache_key: None,
+            freshness_lifetime_secs: Some(3600),
+            freshness_source: Some(FreshnessSource::MaxAge),
+            current_age_secs: 45,
+            ttl_remaining_secs: Some(3555),
+            is_fresh: true,
+            browser_cache: Cacheability { cacheable: true, reason: "fresh for 3600s (max-age)".to_string() },
+            shared_cache: Cacheability { cacheable: true, reason: "fresh for 3600s (max-age)".to_string() },
+            vary_analysis: None,
+            query_key: None,
+            method_comparison: None,
+            credential_impact: None,
+            origin_comparison: None,
+            fan_out: None,
+            warmup: None,
+            probe_warnings: Vec::new(),
             is_cached: true,
-            cache_verdict: "HIT - Served from fastly cache".to_string(),
+            cache_status: CacheStatus::Hit,
+            cache_verdict: "Served from fastly cache".to_string(),
+            timings: Timings::default(),
         };
 
         assert_eq!(result.is_cached, true);
-        assert!(result.cache_verdict.contains("HIT"));
+        assert_eq!(result.cache_status, CacheStatus::Hit);
         assert_eq!(result.x_cache, Some("HIT".to_string()));
     }
 
@@ -103,46 +242,108 @@ mod cdn_detection_tests {
     fn test_no_cache_headers() {
         let result = CacheResult {
             url: "https://example.com".to_string(),
+            request_method: RequestMethod::Get,
+            redirect_chain: Vec::new(),
             cache_control: "None".to_string(),
+            directives: CacheDirectives::default(),
+            targeted_cache_control: Vec::new(),
+            edge_ttl_header: "cache-control".to_string(),
+            surrogate_keys: Vec::new(),
             etag: "None".to_string(),
             last_modified: "None".to_string(),
             expires: "None".to_string(),
             cdn_provider: "Unknown".to_string(),
+            cdn_details: BTreeMap::new(),
+            cdn_request_id: None,
+            edge_location: None,
+            cache_tiers: Vec::new(),
+            cache_status_layers: Vec::new(),
             cf_cache_status: None,
             cf_ray: None,
             x_cache: None,
             x_served_by: None,
             age: None,
             x_cache_hits: None,
+            x_check_cacheable: None,
+            x_cache_key: None,
+            x_true_cache_key: None,
+            freshness_lifetime_secs: None,
+            freshness_source: None,
+            current_age_secs: 0,
+            ttl_remaining_secs: None,
+            is_fresh: false,
+            browser_cache: Cacheability { cacheable: false, reason: "no freshness information".to_string() },
+            shared_cache: Cacheability { cacheable: false, reason: "no freshness information".to_string() },
+            vary_analysis: None,
+            query_key: None,
+            method_comparison: None,
+            credential_impact: None,
+            origin_comparison: None,
+            fan_out: None,
+            warmup: None,
+            probe_warnings: Vec::new(),
             is_cached: false,
-            cache_verdict: "Not cached - No cache indicators".to_string(),
+            cache_status: CacheStatus::Uncacheable,
+            cache_verdict: "No cache indicators".to_string(),
+            timings: Timings::default(),
         };
 
         assert_eq!(result.is_cached, false);
-        assert!(result.cache_verdict.contains("Not cached"));
+        assert_eq!(result.cache_status, CacheStatus::Uncacheable);
     }
 
     #[test]
     fn test_standard_cache_headers_present() {
         let result = CacheResult {
             url: "https://example.com".to_string(),
+            request_method: RequestMethod::Get,
+            redirect_chain: Vec::new(),
             cache_control: "max-age=7200, public".to_string(),
+            directives: CacheDirectives::parse("max-age=7200, public"),
+            targeted_cache_control: Vec::new(),
+            edge_ttl_header: "cache-control".to_string(),
+            surrogate_keys: Vec::new(),
             etag: "\"def456\"".to_string(),
             last_modified: "Mon, 01 Jan 2024 00:00:00 GMT".to_string(),
             expires: "None".to_string(),
             cdn_provider: "Unknown".to_string(),
+            cdn_details: BTreeMap::new(),
+            cdn_request_id: None,
+            edge_location: None,
+            cache_tiers: Vec::new(),
+            cache_status_layers: Vec::new(),
             cf_cache_status: None,
             cf_ray: None,
             x_cache: None,
             x_served_by: None,
             age: None,
             x_cache_hits: None,
+            x_check_cacheable: None,
+            x_cache_key: None,
+            x_true_cache_key: None,
+            freshness_lifetime_secs: Some(7200),
+            freshness_source: Some(FreshnessSource::MaxAge),
+            current_age_secs: 0,
+            ttl_remaining_secs: Some(7200),
+            is_fresh: true,
+            browser_cache: Cacheability { cacheable: true, reason: "fresh for 7200s (max-age)".to_string() },
+            shared_cache: Cacheability { cacheable: true, reason: "fresh for 7200s (max-age)".to_string() },
+            vary_analysis: None,
+            query_key: None,
+            method_comparison: None,
+            credential_impact: None,
+            origin_comparison: None,
+            fan_out: None,
+            warmup: None,
+            probe_warnings: Vec::new(),
             is_cached: true,
-            cache_verdict: "Cacheable - Has cache headers".to_string(),
+            cache_status: CacheStatus::Cacheable,
+            cache_verdict: "Has cache headers, fresh for 7200s (max-age)".to_string(),
+            timings: Timings::default(),
         };
 
         assert_eq!(result.is_cached, true);
-        assert!(result.cache_verdict.contains("Cacheable"));
+        assert_eq!(result.cache_status, CacheStatus::Cacheable);
     }
 }
 
@@ -155,25 +356,57 @@ mod serialization_tests {
     fn test_cache_result_serialization() {
         let result = CacheResult {
             url: "https://example.com".to_string(),
+            request_method: RequestMethod::Get,
+            redirect_chain: Vec::new(),
             cache_control: "max-age=3600".to_string(),
+            directives: CacheDirectives::parse("max-age=3600"),
+            targeted_cache_control: Vec::new(),
+            edge_ttl_header: "cache-control".to_string(),
+            surrogate_keys: Vec::new(),
             etag: "\"abc123\"".to_string(),
             last_modified: "None".to_string(),
             expires: "None".to_string(),
             cdn_provider: "cloudflare".to_string(),
+            cdn_details: BTreeMap::new(),
+            cdn_request_id: Some("12345".to_string()),
+            edge_location: None,
+            cache_tiers: Vec::new(),
+            cache_status_layers: Vec::new(),
             cf_cache_status: Some("HIT".to_string()),
             cf_ray: Some("12345".to_string()),
             x_cache: None,
             x_served_by: None,
             age: Some("120".to_string()),
             x_cache_hits: None,
+            x_check_cacheable: None,
+            x_cache_key: None,
+            x_true_cache_key: None,
+            freshness_lifetime_secs: Some(3600),
+            freshness_source: Some(FreshnessSource::MaxAge),
+            current_age_secs: 120,
+            ttl_remaining_secs: Some(3480),
+            is_fresh: true,
+            browser_cache: Cacheability { cacheable: true, reason: "fresh for 3600s (max-age)".to_string() },
+            shared_cache: Cacheability { cacheable: true, reason: "fresh for 3600s (max-age)".to_string() },
+            vary_analysis: None,
+            query_key: None,
+            method_comparison: None,
+            credential_impact: None,
+            origin_comparison: None,
+            fan_out: None,
+            warmup: None,
+            probe_warnings: Vec::new(),
             is_cached: true,
-            cache_verdict: "HIT - Served from Cloudflare cache".to_string(),
+            cache_status: CacheStatus::Hit,
+            cache_verdict: "Served from Cloudflare cache".to_string(),
+            timings: Timings::default(),
         };
 
         let json = serde_json::to_string(&result).unwrap();
         assert!(json.contains("\"url\":\"https://example.com\""));
         assert!(json.contains("\"is_cached\":true"));
         assert!(json.contains("\"cf_cache_status\":\"HIT\""));
+        assert!(json.contains("\"cache_status\":\"HIT\""));
         assert!(json.contains("\"cdn_provider\":\"cloudflare\""));
     }
 
@@ -181,19 +414,50 @@ mod serialization_tests {
     fn test_cache_result_with_null_optional_fields() {
         let result = CacheResult {
             url: "https://example.com".to_string(),
+            request_method: RequestMethod::Get,
+            redirect_chain: Vec::new(),
             cache_control: "None".to_string(),
+            directives: CacheDirectives::default(),
+            targeted_cache_control: Vec::new(),
+            edge_ttl_header: "cache-control".to_string(),
+            surrogate_keys: Vec::new(),
             etag: "None".to_string(),
             last_modified: "None".to_string(),
             expires: "None".to_string(),
             cdn_provider: "Unknown".to_string(),
+            cdn_details: BTreeMap::new(),
+            cdn_request_id: None,
+            edge_location: None,
+            cache_tiers: Vec::new(),
+            cache_status_layers: Vec::new(),
             cf_cache_status: None,
             cf_ray: None,
             x_cache: None,
             x_served_by: None,
             age: None,
             x_cache_hits: None,
+            x_check_cacheable: None,
+            x_cache_key: None,
+            x_true_cache_key: None,
+            freshness_lifetime_secs: None,
+            freshness_source: None,
+            current_age_secs: 0,
+            ttl_remaining_secs: None,
+            is_fresh: false,
+            browser_cache: Cacheability { cacheable: false, reason: "no freshness information".to_string() },
+            shared_cache: Cacheability { cacheable: false, reason: "no freshness information".to_string() },
+            vary_analysis: None,
+            query_key: None,
+            method_comparison: None,
+            credential_impact: None,
+            origin_comparison: None,
+            fan_out: None,
+            warmup: None,
+            probe_warnings: Vec::new(),
             is_cached: false,
-            cache_verdict: "Not cached".to_string(),
+            cache_status: CacheStatus::Uncacheable,
+            cache_verdict: "No cache indicators".to_string(),
+            timings: Timings::default(),
         };
 
         let json = serde_json::to_string(&result).unwrap();
@@ -201,3 +465,66 @@ mod serialization_tests {
         assert!(json.contains("\"is_cached\":false"));
     }
 }
+
+#[cfg(test)]
+mod cache_status_tests {
+    use super::*;
+
+    #[test]
+    fn test_status_keywords_are_case_insensitive() {
+        assert_eq!(CacheStatus::from_keyword("HIT"), Some(CacheStatus::Hit));
+        assert_eq!(CacheStatus::from_keyword(" Revalidated "), Some(CacheStatus::Revalidated));
+        assert_eq!(CacheStatus::from_keyword("weird"), None);
+    }
+
+    #[test]
+    fn test_status_serializes_as_stable_machine_value() {
+        assert_eq!(serde_json::to_string(&CacheStatus::Uncacheable).unwrap(), "\"UNCACHEABLE\"");
+        assert_eq!(CacheStatus::Stale.to_string(), "STALE");
+        assert!(CacheStatus::Stale.is_cached());
+        assert!(!CacheStatus::Expired.is_cached());
+    }
+
+    #[test]
+    fn test_browser_only_response_is_not_cached() {
+        let h = headers(&[("cache-control", "private, max-age=600")]);
+        let verdict = response_verdict(&h, &CdnRegistry::default(), false);
+
+        assert_eq!(verdict.status, CacheStatus::Private);
+        assert!(!verdict.status.is_cached());
+    }
+}
+
+#[cfg(test)]
+mod side_probe_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_failed_origin_comparison_keeps_the_scan() {
+        let route = warp::any().map(|| warp::reply::with_header("ok", "cache-control", "public, max-age=60"));
+        let (addr, server) = warp::serve(route).bind_ephemeral(([127, 0, 0, 1], 0));
+        tokio::spawn(server);
+
+        let client = SniperClient::builder().retry(RetryPolicy { max_retries: 0, ..RetryPolicy::default() }).build().unwrap();
+        // Nothing listens on 127.0.0.2, so the origin request is refused
+        let options = ScanOptions { client, origin_ip: Some("127.0.0.2".parse().unwrap()), ..ScanOptions::default() };
+        let result = check_cache(&format!("http://localhost:{}/", addr.port()), &options).await.unwrap();
+
+        assert_eq!(result.cache_status, CacheStatus::Cacheable);
+        assert!(result.origin_comparison.is_none());
+        assert!(result.probe_warnings[0].contains("Origin comparison with 127.0.0.2 skipped"));
+    }
+    #[tokio::test]
+    async fn test_failed_fan_out_keeps_the_scan() {
+        let route = warp::any().map(|| warp::reply::with_header("ok", "cache-control", "public, max-age=60"));
+        let (addr, server) = warp::serve(route).bind_ephemeral(([127, 0, 0, 1], 0));
+        tokio::spawn(server);
+
+        let options = ScanOptions { fan_out: Some(FanOutTargets::Addresses(Vec::new())), ..ScanOptions::default() };
+        let result = check_cache(&format!("http://{}/", addr), &options).await.unwrap();
+
+        assert_eq!(result.cache_status, CacheStatus::Cacheable);
+        assert!(result.fan_out.is_none());
+        assert!(result.probe_warnings[0].contains("No addresses to scan"));
+    }
+}
@@ -0,0 +1,130 @@
+mod common;
+
+use cache_sniper::cache_control::{self, CacheDirectives, DEFAULT_TARGET_LIST};
+use cache_sniper::cdn::{CdnProvider, Cloudflare, Fastly};
+use common::headers;
+
+#[cfg(test)]
+mod directive_parsing_tests {
+    use super::*;
+
+    #[test]
+    fn test_parses_common_directives() {
+        let d = CacheDirectives::parse("public, max-age=3600, s-maxage=600, must-revalidate, immutable");
+
+        assert!(d.public);
+        assert_eq!(d.max_age, Some(3600));
+        assert_eq!(d.s_maxage, Some(600));
+        assert!(d.must_revalidate);
+        assert!(d.immutable);
+        assert!(!d.no_store);
+    }
+
+    #[test]
+    fn test_s_maxage_is_not_mistaken_for_max_age() {
+        let d = CacheDirectives::parse("s-maxage=120");
+
+        assert_eq!(d.max_age, None);
+        assert_eq!(d.s_maxage, Some(120));
+    }
+
+    #[test]
+    fn test_qualified_no_cache_keeps_field_list() {
+        let d = CacheDirectives::parse("max-age=60, no-cache=\"Set-Cookie, X-Token\"");
+
+        assert!(d.no_cache);
+        assert_eq!(d.no_cache_fields, vec!["set-cookie", "x-token"]);
+        assert!(!d.requires_revalidation());
+        assert_eq!(d.max_age, Some(60));
+    }
+
+    #[test]
+    fn test_unqualified_no_cache_requires_revalidation() {
+        let d = CacheDirectives::parse("No-Cache");
+
+        assert!(d.no_cache);
+        assert!(d.requires_revalidation());
+    }
+
+    #[test]
+    fn test_private_with_and_without_fields() {
+        assert!(CacheDirectives::parse("private, max-age=600").is_private());
+
+        let qualified = CacheDirectives::parse("private=\"Set-Cookie\"");
+        assert!(qualified.private);
+        assert!(!qualified.is_private());
+    }
+
+    #[test]
+    fn test_stale_extensions_and_unknown_directives() {
+        let d = CacheDirectives::parse("max-age=1, stale-while-revalidate=30, stale-if-error=86400, community=\"UCI\", foo");
+
+        assert_eq!(d.stale_while_revalidate, Some(30));
+        assert_eq!(d.stale_if_error, Some(86400));
+        assert_eq!(d.extensions.len(), 2);
+        assert_eq!(d.extensions[0].name, "community");
+        assert_eq!(d.extensions[0].value.as_deref(), Some("UCI"));
+        assert_eq!(d.extensions[1].value, None);
+    }
+
+    #[test]
+    fn test_invalid_and_duplicate_delta_seconds() {
+        assert_eq!(CacheDirectives::parse("max-age=abc").max_age, Some(0));
+        assert_eq!(CacheDirectives::parse("max-age=10, max-age=20").max_age, Some(10));
+        assert_eq!(CacheDirectives::parse("max-age=99999999999999999999").max_age, Some(2_147_483_648));
+    }
+
+    #[test]
+    fn test_empty_header() {
+        assert!(CacheDirectives::parse("").is_empty());
+        assert!(CacheDirectives::parse(" , ").is_empty());
+    }
+}
+
+#[cfg(test)]
+mod targeted_field_tests {
+    use super::*;
+
+    #[test]
+    fn test_targeted_fields_are_parsed() {
+        let h = headers(&[
+            ("cache-control", "private, max-age=60"),
+            ("cdn-cache-control", "max-age=3600"),
+            ("surrogate-control", "max-age=86400"),
+        ]);
+        let fields = cache_control::targeted_fields(&h);
+
+        assert_eq!(fields.len(), 2);
+        assert_eq!(fields[0].header, "cdn-cache-control");
+        assert_eq!(fields[0].directives.max_age, Some(3600));
+        assert_eq!(fields[1].header, "surrogate-control");
+    }
+
+    #[test]
+    fn test_most_specific_field_for_provider_wins() {
+        let h = headers(&[
+            ("cdn-cache-control", "max-age=3600"),
+            ("cloudflare-cdn-cache-control", "max-age=60"),
+            ("surrogate-control", "max-age=86400"),
+        ]);
+        let fields = cache_control::targeted_fields(&h);
+
+        let cloudflare = cache_control::select_targeted(&fields, Cloudflare.target_list()).unwrap();
+        assert_eq!(cloudflare.header, "cloudflare-cdn-cache-control");
+        assert_eq!(cloudflare.directives.max_age, Some(60));
+
+        let fastly = cache_control::select_targeted(&fields, Fastly.target_list()).unwrap();
+        assert_eq!(fastly.header, "surrogate-control");
+
+        let generic = cache_control::select_targeted(&fields, &DEFAULT_TARGET_LIST).unwrap();
+        assert_eq!(generic.header, "cdn-cache-control");
+    }
+
+    #[test]
+    fn test_empty_targeted_field_is_ignored() {
+        let h = headers(&[("cdn-cache-control", " ")]);
+        let fields = cache_control::targeted_fields(&h);
+
+        assert!(cache_control::select_targeted(&fields, &DEFAULT_TARGET_LIST).is_none());
+    }
+}
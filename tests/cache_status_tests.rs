@@ -0,0 +1,82 @@
+mod common;
+
+use cache_sniper::cache_status;
+use cache_sniper::verdict::CacheStatus;
+use common::headers;
+
+#[cfg(test)]
+mod parsing_tests {
+    use super::*;
+
+    #[test]
+    fn test_parses_layers_in_order() {
+        let layers = cache_status::parse("ExampleCDN; hit; ttl=300, Origin; fwd=uri-miss");
+
+        assert_eq!(layers.len(), 2);
+        assert_eq!(layers[0].cache, "ExampleCDN");
+        assert!(layers[0].hit);
+        assert_eq!(layers[0].ttl, Some(300));
+        assert_eq!(layers[1].cache, "Origin");
+        assert!(!layers[1].hit);
+        assert_eq!(layers[1].fwd.as_deref(), Some("uri-miss"));
+    }
+
+    #[test]
+    fn test_parses_all_parameters() {
+        let layers = cache_status::parse(
+            "\"CDN Company Here\"; fwd=stale; fwd-status=304; ttl=-12; stored; collapsed=?1; key=\"/a,b\"; detail=abc",
+        );
+        let layer = &layers[0];
+
+        assert_eq!(layer.cache, "CDN Company Here");
+        assert_eq!(layer.fwd.as_deref(), Some("stale"));
+        assert_eq!(layer.fwd_status, Some(304));
+        assert_eq!(layer.ttl, Some(-12));
+        assert!(layer.stored);
+        assert!(layer.collapsed);
+        assert_eq!(layer.key.as_deref(), Some("/a,b"));
+        assert_eq!(layer.detail.as_deref(), Some("abc"));
+    }
+
+    #[test]
+    fn test_boolean_false_and_malformed_members() {
+        let layers = cache_status::parse("A; hit=?0, %%%, B; hit");
+
+        assert_eq!(layers.len(), 2);
+        assert!(!layers[0].hit);
+        assert_eq!(layers[1].cache, "B");
+        assert!(layers[1].hit);
+    }
+
+    #[test]
+    fn test_multiple_header_lines_are_combined() {
+        let h = headers(&[("cache-status", "OriginCache; hit"), ("cache-status", "Edge; fwd=uri-miss")]);
+
+        assert_eq!(cache_status::from_headers(&h).len(), 2);
+    }
+}
+
+#[cfg(test)]
+mod verdict_tests {
+    use super::*;
+
+    fn status(value: &str) -> Option<CacheStatus> {
+        cache_status::verdict(&cache_status::parse(value)).map(|v| v.status)
+    }
+
+    #[test]
+    fn test_layer_closest_to_client_decides() {
+        assert_eq!(status("Origin; fwd=uri-miss, ExampleCDN; hit; ttl=300"), Some(CacheStatus::Hit));
+        assert_eq!(status("ExampleCDN; hit; ttl=300, Origin; fwd=uri-miss"), Some(CacheStatus::Miss));
+    }
+
+    #[test]
+    fn test_forward_reasons() {
+        assert_eq!(status("Edge; hit; ttl=-5"), Some(CacheStatus::Stale));
+        assert_eq!(status("Edge; fwd=stale; fwd-status=304"), Some(CacheStatus::Revalidated));
+        assert_eq!(status("Edge; fwd=stale; fwd-status=200"), Some(CacheStatus::Expired));
+        assert_eq!(status("Edge; fwd=bypass"), Some(CacheStatus::Bypass));
+        assert_eq!(status("Edge; stored"), None);
+        assert_eq!(status(""), None);
+    }
+}
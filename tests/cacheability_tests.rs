@@ -0,0 +1,72 @@
+mod common;
+
+use cache_sniper::cache_control::{self, CacheDirectives, DEFAULT_TARGET_LIST};
+use cache_sniper::cacheability::{browser_cacheability, shared_cacheability};
+use common::headers;
+use reqwest::header::HeaderMap;
+
+#[cfg(test)]
+mod shared_vs_private_tests {
+    use super::*;
+
+    #[test]
+    fn test_private_is_browser_only() {
+        let d = CacheDirectives::parse("private, max-age=600");
+        let h = HeaderMap::new();
+
+        assert!(browser_cacheability(&d, &h).cacheable);
+        let shared = shared_cacheability(&d, &h, false);
+        assert!(!shared.cacheable);
+        assert!(shared.reason.contains("private"));
+    }
+
+    #[test]
+    fn test_s_maxage_only_applies_to_shared_caches() {
+        let d = CacheDirectives::parse("max-age=0, s-maxage=300");
+        let h = HeaderMap::new();
+
+        assert!(!browser_cacheability(&d, &h).cacheable);
+        assert!(shared_cacheability(&d, &h, false).cacheable);
+    }
+
+    #[test]
+    fn test_authorization_blocks_shared_cache_unless_public() {
+        let h = HeaderMap::new();
+
+        assert!(!shared_cacheability(&CacheDirectives::parse("max-age=60"), &h, true).cacheable);
+        assert!(shared_cacheability(&CacheDirectives::parse("public, max-age=60"), &h, true).cacheable);
+        assert!(shared_cacheability(&CacheDirectives::parse("s-maxage=60"), &h, true).cacheable);
+    }
+
+    #[test]
+    fn test_set_cookie_blocks_shared_cache() {
+        let d = CacheDirectives::parse("max-age=60");
+        let h = headers(&[("set-cookie", "session=abc")]);
+
+        assert!(browser_cacheability(&d, &h).cacheable);
+        assert!(!shared_cacheability(&d, &h, false).cacheable);
+
+        let qualified = CacheDirectives::parse("max-age=60, no-cache=\"Set-Cookie\"");
+        assert!(shared_cacheability(&qualified, &h, false).cacheable);
+    }
+
+    #[test]
+    fn test_no_store_blocks_both() {
+        let d = CacheDirectives::parse("no-store, max-age=60");
+        let h = HeaderMap::new();
+
+        assert!(!browser_cacheability(&d, &h).cacheable);
+        assert!(!shared_cacheability(&d, &h, false).cacheable);
+    }
+
+    #[test]
+    fn test_cdn_cache_control_overrides_private_for_edge() {
+        let h = headers(&[("cache-control", "private, max-age=60"), ("cdn-cache-control", "max-age=600")]);
+        let browser = CacheDirectives::from_headers(&h, "cache-control").unwrap();
+        let fields = cache_control::targeted_fields(&h);
+        let edge = cache_control::select_targeted(&fields, &DEFAULT_TARGET_LIST).unwrap();
+
+        assert!(!shared_cacheability(&browser, &h, false).cacheable);
+        assert!(shared_cacheability(&edge.directives, &h, false).cacheable);
+    }
+}
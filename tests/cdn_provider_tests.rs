@@ -0,0 +1,333 @@
+mod common;
+
+use cache_sniper::cdn::{header, pick, CdnProvider, CdnRegistry};
+use cache_sniper::verdict::{CacheStatus, Verdict};
+use common::headers;
+use reqwest::header::HeaderMap;
+use std::collections::BTreeMap;
+
+fn detected(pairs: &[(&'static str, &str)]) -> Option<String> {
+    CdnRegistry::default()
+        .detect(&headers(pairs))
+        .map(|p| p.name().to_string())
+}
+
+fn status(pairs: &[(&'static str, &str)]) -> Option<CacheStatus> {
+    let h = headers(pairs);
+    let registry = CdnRegistry::default();
+    registry.detect(&h)?.cache_status(&h).map(|v| v.status)
+}
+
+#[cfg(test)]
+mod detection_tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_providers_are_detected() {
+        assert_eq!(detected(&[("cf-ray", "8a1b2c3d4e5f6789-SJC")]).as_deref(), Some("cloudflare"));
+        assert_eq!(detected(&[("x-served-by", "cache-lax-kwhp1940065-LAX"), ("via", "1.1 varnish")]).as_deref(), Some("fastly"));
+        assert_eq!(detected(&[("x-amz-cf-pop", "IAD89-C1")]).as_deref(), Some("cloudfront"));
+        assert_eq!(detected(&[("server", "AkamaiGHost")]).as_deref(), Some("akamai"));
+        assert_eq!(detected(&[("x-azure-ref", "0abc")]).as_deref(), Some("azure"));
+        assert_eq!(detected(&[("via", "1.1 google")]).as_deref(), Some("google"));
+        assert_eq!(detected(&[("server", "BunnyCDN-DE1-123")]).as_deref(), Some("bunnycdn"));
+        assert_eq!(detected(&[("server", "keycdn-engine")]).as_deref(), Some("keycdn"));
+        assert_eq!(detected(&[("x-cache-status", "HIT")]).as_deref(), Some("nginx"));
+        assert_eq!(detected(&[("x-varnish", "32770")]).as_deref(), Some("varnish"));
+    }
+
+    #[test]
+    fn test_plain_origin_is_not_detected() {
+        assert_eq!(detected(&[("server", "Apache")]), None);
+    }
+
+    #[test]
+    fn test_google_frontend_is_not_cloud_cdn() {
+        assert_eq!(detected(&[("server", "Google Frontend"), ("age", "30")]), None);
+    }
+}
+
+#[cfg(test)]
+mod status_mapping_tests {
+    use super::*;
+
+    #[test]
+    fn test_cloudflare_statuses() {
+        assert_eq!(status(&[("cf-cache-status", "HIT")]), Some(CacheStatus::Hit));
+        assert_eq!(status(&[("cf-cache-status", "DYNAMIC")]), Some(CacheStatus::Dynamic));
+        assert_eq!(status(&[("cf-cache-status", "NONE/UNKNOWN")]), Some(CacheStatus::Unknown));
+    }
+
+    #[test]
+    fn test_google_hit_needs_positive_age() {
+        assert_eq!(status(&[("via", "1.1 google"), ("age", "30")]), Some(CacheStatus::Hit));
+        assert_eq!(status(&[("via", "1.1 google"), ("age", "0")]), None);
+        assert_eq!(status(&[("via", "1.1 google")]), None);
+    }
+
+    #[test]
+    fn test_azure_statuses() {
+        assert_eq!(status(&[("x-azure-ref", "0abc"), ("x-cache", "TCP_REMOTE_HIT")]), Some(CacheStatus::Hit));
+        assert_eq!(status(&[("x-azure-ref", "0abc"), ("x-cache", "CONFIG_NOCACHE")]), Some(CacheStatus::Bypass));
+        assert_eq!(status(&[("x-azure-ref", "0abc"), ("x-cache", "PRIVATE_NOSTORE")]), Some(CacheStatus::Uncacheable));
+    }
+
+    #[test]
+    fn test_bunny_and_nginx_statuses() {
+        assert_eq!(status(&[("server", "BunnyCDN-DE1-123"), ("cdn-cache", "EXPIRED")]), Some(CacheStatus::Expired));
+        assert_eq!(status(&[("x-cache-status", "UPDATING")]), Some(CacheStatus::Updating));
+    }
+
+    #[test]
+    fn test_extract_only_returns_present_headers() {
+        let h = headers(&[("cf-ray", "abc-SJC"), ("cf-cache-status", "HIT")]);
+        let details = CdnRegistry::default().detect(&h).unwrap().extract(&h);
+
+        assert_eq!(details.len(), 2);
+        assert_eq!(details.get("cf-ray").map(String::as_str), Some("abc-SJC"));
+    }
+}
+
+#[cfg(test)]
+mod custom_provider_tests {
+    use super::*;
+
+    struct InHouseEdge;
+
+    impl CdnProvider for InHouseEdge {
+        fn name(&self) -> &str {
+            "in-house"
+        }
+
+        fn detect(&self, headers: &HeaderMap) -> bool {
+            headers.contains_key("x-edge-node")
+        }
+
+        fn extract(&self, headers: &HeaderMap) -> BTreeMap<String, String> {
+            pick(headers, &["x-edge-node", "x-edge-result"])
+        }
+
+        fn cache_status(&self, headers: &HeaderMap) -> Option<Verdict> {
+            let result = header(headers, "x-edge-result")?;
+            CacheStatus::from_keyword(result).map(|s| Verdict::new(s, "In-house edge"))
+        }
+    }
+
+    #[test]
+    fn test_registered_provider_takes_precedence() {
+        let mut registry = CdnRegistry::default();
+        registry.register(InHouseEdge);
+
+        // Also looks like Varnish, but the custom provider is consulted first
+        let h = headers(&[("x-edge-node", "edge-7"), ("x-edge-result", "hit"), ("via", "1.1 varnish")]);
+        let provider = registry.detect(&h).unwrap();
+
+        assert_eq!(provider.name(), "in-house");
+        assert_eq!(provider.cache_status(&h).unwrap().status, CacheStatus::Hit);
+        assert_eq!(registry.names()[0], "in-house");
+    }
+
+    #[test]
+    fn test_empty_registry_detects_nothing() {
+        assert!(CdnRegistry::empty().detect(&headers(&[("cf-ray", "x")])).is_none());
+    }
+}
+
+#[cfg(test)]
+mod cloudfront_tests {
+    use super::*;
+    use cache_sniper::cdn::CloudFront;
+
+    #[test]
+    fn test_cloudfront_states() {
+        let cf = |x_cache: &str| {
+            CloudFront
+                .cache_status(&headers(&[("x-cache", x_cache)]))
+                .map(|v| v.status)
+        };
+
+        assert_eq!(cf("Hit from cloudfront"), Some(CacheStatus::Hit));
+        assert_eq!(cf("RefreshHit from cloudfront"), Some(CacheStatus::Revalidated));
+        assert_eq!(cf("Miss from cloudfront"), Some(CacheStatus::Miss));
+        assert_eq!(cf("LimitExceeded from cloudfront"), Some(CacheStatus::Bypass));
+        assert_eq!(cf("Redirect from cloudfront"), Some(CacheStatus::Bypass));
+        assert_eq!(cf("Error from cloudfront"), Some(CacheStatus::Unknown));
+    }
+
+    #[test]
+    fn test_cloudfront_pop_and_request_id() {
+        let h = headers(&[
+            ("x-cache", "Hit from cloudfront"),
+            ("x-amz-cf-pop", "IAD89-C1"),
+            ("x-amz-cf-id", "q6Yx7Zk0aR2=="),
+        ]);
+        let registry = CdnRegistry::default();
+        let provider = registry.detect(&h).unwrap();
+        let location = provider.edge_location(&h).unwrap();
+
+        assert_eq!(provider.name(), "cloudfront");
+        assert_eq!(provider.request_id(&h).as_deref(), Some("q6Yx7Zk0aR2=="));
+        assert_eq!(location.pop, "IAD89-C1");
+        assert_eq!(location.airport.as_deref(), Some("IAD"));
+        assert_eq!(location.city.as_deref(), Some("Washington, D.C."));
+    }
+
+    #[test]
+    fn test_cloudflare_ray_carries_pop() {
+        let h = headers(&[("cf-ray", "8a1b2c3d4e5f6789-SJC")]);
+        let location = CdnRegistry::default().detect(&h).unwrap().edge_location(&h).unwrap();
+
+        assert_eq!(location.airport.as_deref(), Some("SJC"));
+        assert_eq!(location.city.as_deref(), Some("San Jose"));
+    }
+}
+
+#[cfg(test)]
+mod fastly_tier_tests {
+    use super::*;
+    use cache_sniper::cdn::{Fastly, TierRole};
+
+    fn fastly(x_cache: &str, served_by: &str, hits: &str) -> HeaderMap {
+        headers(&[("x-cache", x_cache), ("x-served-by", served_by), ("x-cache-hits", hits)])
+    }
+
+    #[test]
+    fn test_tiers_are_parsed_shield_first() {
+        let h = fastly("MISS, HIT", "cache-iad-1, cache-lax-2", "0, 3");
+        let tiers = Fastly.cache_tiers(&h);
+
+        assert_eq!(tiers.len(), 2);
+        assert_eq!(tiers[0].role, TierRole::Shield);
+        assert_eq!(tiers[0].node.as_deref(), Some("cache-iad-1"));
+        assert_eq!(tiers[0].status, CacheStatus::Miss);
+        assert_eq!(tiers[0].hits, Some(0));
+        assert_eq!(tiers[1].role, TierRole::Edge);
+        assert_eq!(tiers[1].status, CacheStatus::Hit);
+        assert_eq!(tiers[1].hits, Some(3));
+    }
+
+    #[test]
+    fn test_edge_hit_vs_shield_hit_edge_miss() {
+        let edge_hit = Fastly.cache_status(&fastly("MISS, HIT", "cache-iad-1, cache-lax-2", "0, 3")).unwrap();
+        assert_eq!(edge_hit.status, CacheStatus::Hit);
+        assert!(edge_hit.reason.starts_with("Edge HIT"));
+
+        let shield_hit = Fastly.cache_status(&fastly("HIT, MISS", "cache-iad-1, cache-lax-2", "5, 0")).unwrap();
+        assert_eq!(shield_hit.status, CacheStatus::Hit);
+        assert!(shield_hit.reason.starts_with("Shield HIT, edge MISS"));
+
+        let all_miss = Fastly.cache_status(&fastly("MISS, MISS", "cache-iad-1, cache-lax-2", "0, 0")).unwrap();
+        assert_eq!(all_miss.status, CacheStatus::Miss);
+    }
+
+    #[test]
+    fn test_single_tier_and_edge_location() {
+        let h = fastly("HIT", "cache-lax-kwhp1940065-LAX", "1");
+        let tiers = Fastly.cache_tiers(&h);
+        let location = Fastly.edge_location(&h).unwrap();
+
+        assert_eq!(tiers.len(), 1);
+        assert_eq!(tiers[0].role, TierRole::Edge);
+        assert_eq!(location.pop, "cache-lax-kwhp1940065-LAX");
+        assert_eq!(location.city.as_deref(), Some("Los Angeles"));
+    }
+
+    #[test]
+    fn test_pass_is_bypass() {
+        assert_eq!(Fastly.cache_status(&fastly("PASS", "cache-lax-2", "0")).unwrap().status, CacheStatus::Bypass);
+    }
+}
+
+#[cfg(test)]
+mod akamai_tests {
+    use super::*;
+    use cache_sniper::cdn::{Akamai, TierRole};
+
+    fn akamai_status(x_cache: &str, cacheable: &str) -> CacheStatus {
+        let h = headers(&[("x-cache", x_cache), ("x-check-cacheable", cacheable)]);
+        Akamai.cache_status(&h).unwrap().status
+    }
+
+    #[test]
+    fn test_tcp_codes() {
+        let from = " from a23-45-67-89.deploy.akamaitechnologies.com (AkamaiGHost/22.1.0-1) (-)";
+
+        assert_eq!(akamai_status(&format!("TCP_HIT{}", from), "YES"), CacheStatus::Hit);
+        assert_eq!(akamai_status(&format!("TCP_MEM_HIT{}", from), "YES"), CacheStatus::Hit);
+        assert_eq!(akamai_status(&format!("TCP_REFRESH_HIT{}", from), "YES"), CacheStatus::Revalidated);
+        assert_eq!(akamai_status(&format!("TCP_MISS{}", from), "YES"), CacheStatus::Miss);
+        assert_eq!(akamai_status(&format!("TCP_MISS{}", from), "NO"), CacheStatus::Uncacheable);
+    }
+
+    #[test]
+    fn test_debug_headers_are_detected_and_extracted() {
+        let h = headers(&[
+            ("x-cache", "TCP_MISS from a23-45-67-89.deploy.akamaitechnologies.com"),
+            ("x-cache-remote", "TCP_HIT from a1-2-3-4.deploy.akamaitechnologies.com"),
+            ("x-check-cacheable", "YES"),
+            ("x-cache-key", "S/L/1234/567890/1d/www.example.com/index.html"),
+        ]);
+        let registry = CdnRegistry::default();
+        let provider = registry.detect(&h).unwrap();
+        let tiers = provider.cache_tiers(&h);
+
+        assert_eq!(provider.name(), "akamai");
+        assert!(provider.extract(&h).contains_key("x-cache-key"));
+        assert_eq!(tiers.len(), 2);
+        assert_eq!(tiers[0].role, TierRole::Shield);
+        assert_eq!(tiers[0].status, CacheStatus::Hit);
+        assert_eq!(tiers[0].node.as_deref(), Some("a1-2-3-4.deploy.akamaitechnologies.com"));
+        assert_eq!(tiers[1].status, CacheStatus::Miss);
+    }
+}
+
+#[cfg(test)]
+mod reverse_proxy_tests {
+    use super::*;
+
+    #[test]
+    fn test_varnish_transaction_ids() {
+        assert_eq!(status(&[("x-varnish", "32770")]), Some(CacheStatus::Miss));
+        assert_eq!(status(&[("x-varnish", "32770 32768")]), Some(CacheStatus::Hit));
+    }
+
+    #[test]
+    fn test_varnish_via_only_falls_back_to_x_cache() {
+        assert_eq!(detected(&[("via", "1.1 varnish (Varnish/7.4)")]).as_deref(), Some("varnish"));
+        assert_eq!(status(&[("via", "1.1 varnish (Varnish/7.4)"), ("x-cache", "HIT")]), Some(CacheStatus::Hit));
+        assert_eq!(status(&[("via", "1.1 varnish (Varnish/7.4)")]), None);
+    }
+
+    #[test]
+    fn test_nginx_upstream_cache_statuses() {
+        for (value, expected) in [
+            ("HIT", CacheStatus::Hit),
+            ("MISS", CacheStatus::Miss),
+            ("EXPIRED", CacheStatus::Expired),
+            ("STALE", CacheStatus::Stale),
+            ("UPDATING", CacheStatus::Updating),
+            ("REVALIDATED", CacheStatus::Revalidated),
+            ("BYPASS", CacheStatus::Bypass),
+        ] {
+            assert_eq!(status(&[("x-cache-status", value)]), Some(expected), "{}", value);
+        }
+        assert_eq!(status(&[("x-proxy-cache", "HIT")]), Some(CacheStatus::Hit));
+    }
+
+    #[test]
+    fn test_squid_lookup() {
+        assert_eq!(detected(&[("x-cache-lookup", "MISS from proxy:3128")]).as_deref(), Some("squid"));
+        assert_eq!(
+            status(&[("x-cache", "HIT from proxy"), ("x-cache-lookup", "HIT from proxy:3128")]),
+            Some(CacheStatus::Hit)
+        );
+        assert_eq!(
+            status(&[("x-cache", "MISS from proxy"), ("x-cache-lookup", "HIT from proxy:3128")]),
+            Some(CacheStatus::Expired)
+        );
+        assert_eq!(
+            status(&[("x-cache", "MISS from proxy"), ("x-cache-lookup", "MISS from proxy:3128")]),
+            Some(CacheStatus::Miss)
+        );
+    }
+}
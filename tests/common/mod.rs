@@ -0,0 +1,10 @@
+use reqwest::header::{HeaderMap, HeaderValue};
+
+/// Builds a `HeaderMap` from name/value pairs, keeping repeated names
+pub fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+    let mut map = HeaderMap::new();
+    for (name, value) in pairs {
+        map.append(*name, HeaderValue::from_str(value).unwrap());
+    }
+    map
+}
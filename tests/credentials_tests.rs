@@ -0,0 +1,86 @@
+mod common;
+
+use cache_sniper::cache_checker::response_verdict;
+use cache_sniper::cdn::CdnRegistry;
+use cache_sniper::credentials::{self, CredentialImpact};
+use cache_sniper::verdict::CacheStatus;
+use common::headers;
+
+#[cfg(test)]
+mod header_option_tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_header_line() {
+        let (name, value) = credentials::parse_header_line("Accept-Encoding:  br, gzip ").unwrap();
+
+        assert_eq!(name.as_str(), "accept-encoding");
+        assert_eq!(value, "br, gzip");
+        assert!(credentials::parse_header_line("no colon here").is_err());
+        assert!(credentials::parse_header_line("bad name: x").is_err());
+    }
+
+    #[test]
+    fn test_auth_values() {
+        assert_eq!(credentials::basic_auth("aladdin:opensesame").unwrap(), "Basic YWxhZGRpbjpvcGVuc2VzYW1l");
+        assert!(credentials::basic_auth("no-password").is_err());
+
+        let bearer = credentials::bearer("abc123").unwrap();
+        assert_eq!(bearer, "Bearer abc123");
+        assert!(bearer.is_sensitive());
+    }
+
+    #[test]
+    fn test_cookies_join_a_cookie_header() {
+        let lines = [credentials::parse_header_line("Cookie: a=1").unwrap()];
+        let h = credentials::request_headers(&lines, &["b=2".to_string()], None).unwrap();
+
+        assert_eq!(h.get_all("cookie").iter().count(), 1);
+        assert_eq!(h["cookie"], "a=1; b=2");
+    }
+
+    #[test]
+    fn test_authorization_from_two_sources_is_an_error() {
+        let bearer = credentials::bearer("abc123").unwrap();
+        let lines = [credentials::parse_header_line("Authorization: Basic eDp5").unwrap()];
+
+        assert!(credentials::request_headers(&lines, &[], Some(&bearer)).is_err());
+        assert_eq!(credentials::request_headers(&lines, &[], None).unwrap()["authorization"], "Basic eDp5");
+        assert_eq!(credentials::request_headers(&[], &[], Some(&bearer)).unwrap()["authorization"], "Bearer abc123");
+    }
+
+    #[test]
+    fn test_anonymous_strips_only_credentials() {
+        let h = headers(&[("authorization", "Bearer x"), ("cookie", "a=1"), ("accept-encoding", "gzip")]);
+
+        assert_eq!(credentials::sent_credentials(&h), vec!["authorization", "cookie"]);
+        let anonymous = credentials::anonymous(&h);
+        assert!(credentials::sent_credentials(&anonymous).is_empty());
+        assert!(anonymous.contains_key("accept-encoding"));
+    }
+}
+
+#[cfg(test)]
+mod credential_impact_tests {
+    use super::*;
+
+    #[test]
+    fn test_authorization_makes_shared_cache_refuse() {
+        let h = headers(&[("cache-control", "max-age=600")]);
+        let registry = CdnRegistry::default();
+
+        assert_eq!(response_verdict(&h, &registry, false).status, CacheStatus::Cacheable);
+        let with_auth = response_verdict(&h, &registry, true);
+        assert_eq!(with_auth.status, CacheStatus::Private);
+        assert!(with_auth.reason.contains("Browser only"));
+    }
+
+    #[test]
+    fn test_impact_reports_change() {
+        let impact = CredentialImpact::new(vec!["cookie".to_string()], CacheStatus::Bypass, CacheStatus::Hit, "HIT".to_string());
+        assert!(impact.changed);
+
+        let impact = CredentialImpact::new(vec!["cookie".to_string()], CacheStatus::Hit, CacheStatus::Hit, "HIT".to_string());
+        assert!(!impact.changed);
+    }
+}
@@ -0,0 +1,108 @@
+use cache_sniper::error::{ErrorKind, ScanError};
+use cache_sniper::http_client::{RequestMethod, RetryPolicy, SniperClient};
+use reqwest::header::HeaderMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use warp::http::StatusCode;
+use warp::Filter;
+
+fn fast_retries(max_retries: u32) -> RetryPolicy {
+    RetryPolicy { max_retries, base_delay: Duration::from_millis(1), max_delay: Duration::from_millis(5) }
+}
+
+#[cfg(test)]
+mod backoff_tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_doubles_up_to_the_cap() {
+        let policy = RetryPolicy { max_retries: 5, base_delay: Duration::from_millis(100), max_delay: Duration::from_millis(350) };
+
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(3), Duration::from_millis(350));
+        assert_eq!(policy.backoff(40), Duration::from_millis(350));
+    }
+
+    #[test]
+    fn test_jitter_stays_within_half_and_full_backoff() {
+        let policy = RetryPolicy::default();
+        for _ in 0..100 {
+            let delay = policy.delay(2);
+            assert!(delay >= policy.backoff(2) / 2 && delay <= policy.backoff(2));
+        }
+    }
+
+    #[test]
+    fn test_only_network_failures_are_transient() {
+        assert!(ErrorKind::Timeout.is_transient());
+        assert!(!ErrorKind::HttpStatus.is_transient());
+        assert!(!ErrorKind::Tls.is_transient());
+        assert!(!ErrorKind::InvalidUrl.is_transient());
+    }
+
+    #[test]
+    fn test_error_serializes_kind() {
+        let json = serde_json::to_value(ScanError::http_status(503).with_attempts(3)).unwrap();
+
+        assert_eq!(json["kind"], "http_status");
+        assert_eq!(json["status"], 503);
+        assert_eq!(json["attempts"], 3);
+    }
+}
+
+#[cfg(test)]
+mod retry_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_retries_until_the_server_recovers() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&calls);
+        let route = warp::any().map(move || {
+            let status = if counter.fetch_add(1, Ordering::SeqCst) < 2 { StatusCode::SERVICE_UNAVAILABLE } else { StatusCode::OK };
+            warp::reply::with_status("body", status)
+        });
+        let (addr, server) = warp::serve(route).bind_ephemeral(([127, 0, 0, 1], 0));
+        tokio::spawn(server);
+
+        let client = SniperClient::builder().retry(fast_retries(2)).build().unwrap();
+        let fetched = client.fetch_headers(&format!("http://{}/", addr), RequestMethod::Get, &HeaderMap::new()).await.unwrap();
+
+        assert_eq!(fetched.status, 200);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_returns_the_last_response_when_retries_run_out() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&calls);
+        let route = warp::any().map(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+            warp::reply::with_header(warp::reply::with_status("busy", StatusCode::SERVICE_UNAVAILABLE), "age", "30")
+        });
+        let (addr, server) = warp::serve(route).bind_ephemeral(([127, 0, 0, 1], 0));
+        tokio::spawn(server);
+
+        let client = SniperClient::builder().retry(fast_retries(1)).build().unwrap();
+        let fetched = client.fetch_headers(&format!("http://{}/", addr), RequestMethod::Get, &HeaderMap::new()).await.unwrap();
+
+        assert_eq!(fetched.status, 503);
+        assert_eq!(fetched.headers["age"], "30");
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_refused_connection_is_a_connect_error() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+
+        let client = SniperClient::builder().retry(fast_retries(0)).build().unwrap();
+        let error = client.fetch_headers(&format!("http://{}/", addr), RequestMethod::Get, &HeaderMap::new()).await.unwrap_err();
+
+        assert_eq!(error.kind, ErrorKind::Connect);
+        assert_eq!(error.attempts, 1);
+    }
+}
@@ -0,0 +1,86 @@
+mod common;
+
+use cache_sniper::cdn::CdnRegistry;
+use cache_sniper::fan_out::{self, FanOutReport, FanOutTargets, IpScan};
+use cache_sniper::http_client::{RequestMethod, SniperClient};
+use cache_sniper::verdict::CacheStatus;
+use common::headers;
+use reqwest::header::HeaderMap;
+use std::net::IpAddr;
+use warp::Filter;
+
+fn ip(value: &str) -> IpAddr {
+    value.parse().unwrap()
+}
+
+#[cfg(test)]
+mod summary_tests {
+    use super::*;
+
+    #[test]
+    fn test_per_address_row_from_response() {
+        let h = headers(&[("x-cache", "Hit from cloudfront"), ("x-amz-cf-pop", "FRA56-P1"), ("age", "42")]);
+        let scan = IpScan::from_response(ip("192.0.2.1"), 200, &h, &CdnRegistry::default(), false);
+
+        assert_eq!(scan.cache_status, Some(CacheStatus::Hit));
+        assert_eq!(scan.age.as_deref(), Some("42"));
+        assert_eq!(scan.pop.as_deref(), Some("FRA56-P1"));
+    }
+
+    #[test]
+    fn test_mixed_statuses_are_inconsistent() {
+        let registry = CdnRegistry::default();
+        let hit = headers(&[("cf-cache-status", "HIT")]);
+        let miss = headers(&[("cf-cache-status", "MISS")]);
+        let report = FanOutReport::summarize(vec![
+            IpScan::from_response(ip("192.0.2.1"), 200, &hit, &registry, false),
+            IpScan::from_response(ip("192.0.2.2"), 200, &hit, &registry, false),
+            IpScan::from_response(ip("192.0.2.3"), 200, &miss, &registry, false),
+        ]);
+
+        assert!(!report.consistent);
+        assert_eq!(report.status_counts["HIT"], 2);
+        assert_eq!(report.status_counts["MISS"], 1);
+    }
+
+    #[test]
+    fn test_failed_address_is_never_consistent() {
+        let report = FanOutReport::summarize(vec![IpScan::failed(ip("192.0.2.1"), "connection refused")]);
+
+        assert!(!report.consistent);
+        assert_eq!(report.status_counts["ERROR"], 1);
+    }
+}
+
+#[cfg(test)]
+mod scan_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_fan_out_keeps_address_order() {
+        let route = warp::any().map(|| warp::reply::with_header("ok", "x-cache-status", "HIT"));
+        let (addr, server) = warp::serve(route).bind_ephemeral(([127, 0, 0, 1], 0));
+        tokio::spawn(server);
+
+        let url = format!("http://edge.invalid:{}/", addr.port());
+        let addrs = fan_out::target_addresses(&url, &FanOutTargets::Addresses(vec![ip("127.0.0.1"), ip("::1")]))
+            .await
+            .unwrap();
+        let report = fan_out::fan_out(
+            &url,
+            &addrs,
+            &SniperClient::default(),
+            RequestMethod::Get,
+            &HeaderMap::new(),
+            &CdnRegistry::default(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(report.scans[0].ip, ip("127.0.0.1"));
+        assert_eq!(report.scans[0].cache_status, Some(CacheStatus::Hit));
+        assert_eq!(report.scans[1].ip, ip("::1"));
+        assert!(report.scans[1].error.is_some());
+        assert!(!report.consistent);
+    }
+}
@@ -0,0 +1,125 @@
+mod common;
+
+use cache_sniper::cache_control::CacheDirectives;
+use cache_sniper::freshness::{self, FreshnessSource};
+use common::headers;
+use reqwest::header::HeaderMap;
+use std::time::{Duration, SystemTime};
+
+fn at(http_date: &str) -> SystemTime {
+    httpdate::parse_http_date(http_date).unwrap()
+}
+
+#[cfg(test)]
+mod lifetime_tests {
+    use super::*;
+
+    #[test]
+    fn test_s_maxage_wins_over_max_age_and_expires() {
+        let h = headers(&[
+            ("date", "Mon, 01 Jan 2024 00:00:00 GMT"),
+            ("expires", "Mon, 01 Jan 2024 01:00:00 GMT"),
+        ]);
+        let d = CacheDirectives::parse("max-age=60, s-maxage=300");
+
+        assert_eq!(freshness::freshness_lifetime(&d, &h), Some((300, FreshnessSource::SMaxage)));
+    }
+
+    #[test]
+    fn test_expires_minus_date() {
+        let h = headers(&[
+            ("date", "Mon, 01 Jan 2024 00:00:00 GMT"),
+            ("expires", "Mon, 01 Jan 2024 01:00:00 GMT"),
+        ]);
+
+        assert_eq!(
+            freshness::freshness_lifetime(&CacheDirectives::default(), &h),
+            Some((3600, FreshnessSource::Expires))
+        );
+    }
+
+    #[test]
+    fn test_invalid_expires_is_already_stale() {
+        let h = headers(&[("date", "Mon, 01 Jan 2024 00:00:00 GMT"), ("expires", "0")]);
+
+        assert_eq!(
+            freshness::freshness_lifetime(&CacheDirectives::default(), &h),
+            Some((0, FreshnessSource::Expires))
+        );
+    }
+
+    #[test]
+    fn test_heuristic_from_last_modified() {
+        let h = headers(&[
+            ("date", "Thu, 11 Jan 2024 00:00:00 GMT"),
+            ("last-modified", "Mon, 01 Jan 2024 00:00:00 GMT"),
+        ]);
+
+        // 10% of ten days
+        assert_eq!(
+            freshness::freshness_lifetime(&CacheDirectives::default(), &h),
+            Some((86400, FreshnessSource::Heuristic))
+        );
+    }
+
+    #[test]
+    fn test_missing_date_falls_back_to_response_time() {
+        let received = at("Mon, 01 Jan 2024 00:00:00 GMT");
+        let expires = headers(&[("expires", "Mon, 01 Jan 2024 01:00:00 GMT")]);
+        let last_modified = headers(&[("last-modified", "Fri, 22 Dec 2023 00:00:00 GMT")]);
+
+        assert_eq!(
+            freshness::freshness_lifetime_at(&CacheDirectives::default(), &expires, received),
+            Some((3600, FreshnessSource::Expires))
+        );
+        assert_eq!(
+            freshness::freshness_lifetime_at(&CacheDirectives::default(), &last_modified, received),
+            Some((86400, FreshnessSource::Heuristic))
+        );
+    }
+
+    #[test]
+    fn test_no_lifetime_without_any_hints() {
+        assert_eq!(freshness::freshness_lifetime(&CacheDirectives::default(), &HeaderMap::new()), None);
+    }
+}
+
+#[cfg(test)]
+mod age_tests {
+    use super::*;
+
+    #[test]
+    fn test_current_age_uses_age_header_and_response_delay() {
+        let request_time = at("Mon, 01 Jan 2024 00:00:00 GMT");
+        let response_time = request_time + Duration::from_secs(2);
+        let h = headers(&[("date", "Mon, 01 Jan 2024 00:00:01 GMT"), ("age", "100")]);
+
+        assert_eq!(freshness::current_age(&h, request_time, response_time, response_time), 102);
+    }
+
+    #[test]
+    fn test_current_age_uses_apparent_age_when_larger() {
+        let request_time = at("Mon, 01 Jan 2024 00:10:00 GMT");
+        let h = headers(&[("date", "Mon, 01 Jan 2024 00:00:00 GMT")]);
+
+        assert_eq!(freshness::current_age(&h, request_time, request_time, request_time), 600);
+    }
+
+    #[test]
+    fn test_evaluate_reports_remaining_ttl() {
+        let request_time = at("Mon, 01 Jan 2024 00:00:00 GMT");
+        let h = headers(&[("date", "Mon, 01 Jan 2024 00:00:00 GMT"), ("age", "50")]);
+        let d = CacheDirectives::parse("max-age=60");
+
+        let fresh = freshness::evaluate(&d, &h, request_time, request_time, request_time);
+        assert_eq!(fresh.lifetime_secs, Some(60));
+        assert_eq!(fresh.current_age_secs, 50);
+        assert_eq!(fresh.ttl_remaining_secs, Some(10));
+        assert!(fresh.is_fresh);
+
+        let later = request_time + Duration::from_secs(30);
+        let stale = freshness::evaluate(&d, &h, request_time, request_time, later);
+        assert_eq!(stale.ttl_remaining_secs, Some(-20));
+        assert!(!stale.is_fresh);
+    }
+}
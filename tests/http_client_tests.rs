@@ -0,0 +1,179 @@
+use cache_sniper::http_client::{ConnectTo, HttpVersion, RequestMethod, ResolveOverride, RetryPolicy, SniperClient};
+use reqwest::header::HeaderMap;
+use std::net::IpAddr;
+use std::time::Duration;
+use warp::Filter;
+
+#[cfg(test)]
+mod client_builder_tests {
+    use super::*;
+
+    #[test]
+    fn test_builds_with_custom_settings() {
+        let client = SniperClient::builder()
+            .timeout(Some(Duration::from_secs(5)))
+            .connect_timeout(None)
+            .user_agent("sniper-test/1.0")
+            .max_redirects(0)
+            .proxy("http://127.0.0.1:8080")
+            .insecure(true)
+            .http_version(HttpVersion::Http1Only)
+            .build();
+
+        assert!(client.is_ok());
+    }
+
+    #[test]
+    fn test_missing_root_certificate_is_an_error() {
+        let err = SniperClient::builder()
+            .root_certificate("/nonexistent/ca.pem")
+            .build()
+            .err()
+            .unwrap();
+
+        assert!(err.to_string().contains("/nonexistent/ca.pem"));
+    }
+
+    #[test]
+    fn test_invalid_proxy_is_an_error() {
+        assert!(SniperClient::builder().proxy("not a url").build().is_err());
+    }
+}
+
+#[cfg(test)]
+mod address_override_tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_resolve() {
+        let resolve: ResolveOverride = "Example.com:443:192.0.2.1,[2001:db8::1]".parse().unwrap();
+
+        assert_eq!(resolve.host, "example.com");
+        assert_eq!(resolve.port, 443);
+        assert_eq!(resolve.addrs, vec!["192.0.2.1".parse::<IpAddr>().unwrap(), "2001:db8::1".parse().unwrap()]);
+        assert!("example.com:443".parse::<ResolveOverride>().is_err());
+        assert!("example.com:443:not-an-ip".parse::<ResolveOverride>().is_err());
+    }
+
+    #[test]
+    fn test_parse_connect_to() {
+        let connect: ConnectTo = "example.com:443:[2001:db8::1]:".parse().unwrap();
+
+        assert_eq!(connect.host, "example.com");
+        assert_eq!(connect.port, Some(443));
+        assert_eq!(connect.target_host, "2001:db8::1");
+        assert_eq!(connect.target_port, None);
+        assert!("example.com:443:origin".parse::<ConnectTo>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_connect_to_keeps_a_changed_port() {
+        let connect: ConnectTo = "example.com:443:127.0.0.1:8443".parse().unwrap();
+        assert_eq!(connect.lookup().await.unwrap().target_port, Some(8443));
+
+        let same_port: ConnectTo = "example.com:443:127.0.0.1:443".parse().unwrap();
+        assert_eq!(same_port.lookup().await.unwrap().target_port, None);
+    }
+
+    #[tokio::test]
+    async fn test_connect_to_another_port_keeps_host_header() {
+        let route = warp::header::<String>("host").map(|host: String| warp::reply::with_header("ok", "x-seen-host", host));
+        let (addr, server) = warp::serve(route).bind_ephemeral(([127, 0, 0, 1], 0));
+        tokio::spawn(server);
+
+        let connect: ConnectTo = format!("origin.invalid:80:127.0.0.1:{}", addr.port()).parse().unwrap();
+        let client = SniperClient::builder().resolve(connect.lookup().await.unwrap()).build().unwrap();
+        let fetched = client.fetch_headers("http://origin.invalid/", RequestMethod::Get, &HeaderMap::new()).await.unwrap();
+
+        assert_eq!(fetched.status, 200);
+        assert_eq!(fetched.headers["x-seen-host"], "origin.invalid");
+        assert_eq!(fetched.url, "http://origin.invalid/");
+
+        // Other ports of the host are not redirected
+        let other = client.without_retries().fetch_headers("http://origin.invalid:81/", RequestMethod::Get, &HeaderMap::new()).await;
+        assert!(other.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_connect_to_becomes_resolve_for_its_port() {
+        let connect: ConnectTo = "example.com:8080:127.0.0.1:".parse().unwrap();
+        let resolve = connect.lookup().await.unwrap();
+
+        assert_eq!(resolve.host, "example.com");
+        assert_eq!(resolve.port, 8080);
+        assert_eq!(resolve.addrs, vec!["127.0.0.1".parse::<IpAddr>().unwrap()]);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_sends_requests_to_given_address() {
+        let route = warp::any().map(|| warp::reply::with_header("ok", "cache-control", "max-age=60"));
+        let (addr, server) = warp::serve(route).bind_ephemeral(([127, 0, 0, 1], 0));
+        tokio::spawn(server);
+
+        let resolve = format!("origin.invalid:{}:127.0.0.1", addr.port()).parse().unwrap();
+        let client = SniperClient::builder().resolve(resolve).build().unwrap();
+        let url = format!("http://origin.invalid:{}/", addr.port());
+        let fetched = client.fetch_headers(&url, RequestMethod::Head, &HeaderMap::new()).await.unwrap();
+
+        assert_eq!(fetched.status, 200);
+        assert_eq!(fetched.headers["cache-control"], "max-age=60");
+
+        let pinned = SniperClient::default().pinned("origin.invalid", &[addr.ip()]).unwrap();
+        assert!(pinned.fetch_headers(&url, RequestMethod::Get, &HeaderMap::new()).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_resolve_only_applies_to_its_port() {
+        let route = warp::any().map(|| "ok");
+        let (addr, server) = warp::serve(route).bind_ephemeral(([127, 0, 0, 1], 0));
+        tokio::spawn(server);
+
+        let other_port = addr.port().wrapping_add(1);
+        let resolve = format!("origin.invalid:{}:127.0.0.1", other_port).parse().unwrap();
+        let no_retries = RetryPolicy { max_retries: 0, ..RetryPolicy::default() };
+        let client = SniperClient::builder().resolve(resolve).retry(no_retries).build().unwrap();
+        let url = format!("http://origin.invalid:{}/", addr.port());
+
+        assert!(client.fetch_headers(&url, RequestMethod::Head, &HeaderMap::new()).await.is_err());
+    }
+}
+
+#[cfg(test)]
+mod redirect_chain_tests {
+    use super::*;
+    use reqwest::header::{HeaderValue, AUTHORIZATION, COOKIE};
+    use std::sync::{Arc, Mutex};
+    use warp::http::{Response, StatusCode};
+
+    #[tokio::test]
+    async fn test_credentials_do_not_follow_cross_host_redirects() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let recorder = Arc::clone(&seen);
+        let other = warp::header::headers_cloned().map(move |headers: warp::http::HeaderMap| {
+            recorder.lock().unwrap().push((headers.contains_key("authorization"), headers.contains_key("cookie")));
+            "ok"
+        });
+        let (other_addr, other_server) = warp::serve(other).bind_ephemeral(([127, 0, 0, 1], 0));
+        tokio::spawn(other_server);
+
+        let location = format!("http://{}/landing", other_addr);
+        let start = warp::header::optional::<String>("authorization").map(move |auth: Option<String>| {
+            assert_eq!(auth.as_deref(), Some("Bearer secret"));
+            Response::builder().status(StatusCode::FOUND).header("location", location.as_str()).body(String::new()).unwrap()
+        });
+        let (start_addr, start_server) = warp::serve(start).bind_ephemeral(([127, 0, 0, 1], 0));
+        tokio::spawn(start_server);
+
+        let mut request_headers = HeaderMap::new();
+        request_headers.insert(AUTHORIZATION, HeaderValue::from_static("Bearer secret"));
+        request_headers.insert(COOKIE, HeaderValue::from_static("session=abc"));
+        let hops = SniperClient::default()
+            .fetch_chain(&format!("http://{}/", start_addr), RequestMethod::Get, &request_headers)
+            .await
+            .unwrap();
+
+        assert_eq!(hops.len(), 2);
+        assert_eq!(hops[0].status, 302);
+        assert_eq!(*seen.lock().unwrap(), vec![(false, false)]);
+    }
+}
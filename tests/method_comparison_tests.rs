@@ -0,0 +1,61 @@
+mod common;
+
+use cache_sniper::cdn::CdnRegistry;
+use cache_sniper::http_client::RequestMethod;
+use cache_sniper::method_comparison::MethodComparison;
+use cache_sniper::verdict::CacheStatus;
+use common::headers;
+
+#[cfg(test)]
+mod method_tests {
+    use super::*;
+
+    #[test]
+    fn test_method_names_parse_case_insensitively() {
+        assert_eq!("head".parse::<RequestMethod>(), Ok(RequestMethod::Head));
+        assert_eq!("GET".parse::<RequestMethod>(), Ok(RequestMethod::Get));
+        assert_eq!("Range".parse::<RequestMethod>(), Ok(RequestMethod::GetRange));
+        assert!("POST".parse::<RequestMethod>().is_err());
+    }
+}
+
+#[cfg(test)]
+mod comparison_tests {
+    use super::*;
+
+    #[test]
+    fn test_identical_responses_are_consistent() {
+        let head = headers(&[("cache-control", "max-age=600"), ("etag", "\"v1\""), ("cf-cache-status", "HIT")]);
+        let get = headers(&[
+            ("cache-control", "max-age=600"),
+            ("etag", "\"v1\""),
+            ("cf-cache-status", "HIT"),
+            ("content-length", "5120"),
+        ]);
+        let comparison = MethodComparison::compare(200, &head, 200, &get, &CdnRegistry::default(), false);
+
+        assert!(comparison.consistent);
+        assert!(comparison.differences.is_empty());
+        assert_eq!(comparison.head_cache_status, Some(CacheStatus::Hit));
+    }
+
+    #[test]
+    fn test_differing_cache_headers_are_reported() {
+        let head = headers(&[("cache-control", "no-store"), ("cf-cache-status", "BYPASS")]);
+        let get = headers(&[("cache-control", "max-age=600"), ("cf-cache-status", "HIT")]);
+        let comparison = MethodComparison::compare(200, &head, 200, &get, &CdnRegistry::default(), false);
+
+        assert!(!comparison.consistent);
+        assert_eq!(comparison.differences.len(), 1);
+        assert_eq!(comparison.differences[0].header, "cache-control");
+        assert_eq!(comparison.differences[0].head.as_deref(), Some("no-store"));
+        assert_eq!(comparison.get_cache_status, Some(CacheStatus::Hit));
+    }
+
+    #[test]
+    fn test_status_code_mismatch_is_inconsistent() {
+        let comparison = MethodComparison::compare(405, &headers(&[]), 200, &headers(&[]), &CdnRegistry::default(), false);
+
+        assert!(!comparison.consistent);
+    }
+}
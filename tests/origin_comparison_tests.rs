@@ -0,0 +1,55 @@
+mod common;
+
+use cache_sniper::cdn::CdnRegistry;
+use cache_sniper::origin_comparison::OriginComparison;
+use cache_sniper::verdict::CacheStatus;
+use common::headers;
+use std::net::IpAddr;
+
+#[cfg(test)]
+mod origin_diff_tests {
+    use super::*;
+
+    fn origin_ip() -> IpAddr {
+        "203.0.113.10".parse().unwrap()
+    }
+
+    #[test]
+    fn test_edge_rewrites_cache_control() {
+        let edge = headers(&[
+            ("cache-control", "max-age=60"),
+            ("etag", "\"v2\""),
+            ("cf-cache-status", "HIT"),
+        ]);
+        let origin = headers(&[
+            ("cache-control", "max-age=60"),
+            ("surrogate-control", "max-age=86400"),
+            ("etag", "\"v2\""),
+        ]);
+        let comparison = OriginComparison::compare(origin_ip(), 200, &edge, 200, &origin, &CdnRegistry::default(), false);
+
+        assert_eq!(comparison.differences.len(), 1);
+        assert_eq!(comparison.differences[0].header, "surrogate-control");
+        assert_eq!(comparison.differences[0].edge, None);
+        assert_eq!(comparison.same_object, Some(true));
+        assert_eq!(comparison.edge_cache_status, Some(CacheStatus::Hit));
+    }
+
+    #[test]
+    fn test_stale_edge_object_is_detected() {
+        let edge = headers(&[("etag", "\"v1\""), ("last-modified", "Thu, 11 Jan 2024 10:00:00 GMT")]);
+        let origin = headers(&[("etag", "\"v2\""), ("last-modified", "Thu, 11 Jan 2024 10:00:00 GMT")]);
+        let comparison = OriginComparison::compare(origin_ip(), 200, &edge, 200, &origin, &CdnRegistry::default(), false);
+
+        assert_eq!(comparison.same_object, Some(false));
+    }
+
+    #[test]
+    fn test_no_validators_leaves_object_unknown() {
+        let comparison = OriginComparison::compare(origin_ip(), 200, &headers(&[]), 503, &headers(&[]), &CdnRegistry::default(), false);
+
+        assert_eq!(comparison.same_object, None);
+        assert!(comparison.differences.is_empty());
+        assert_eq!(comparison.origin_status, 503);
+    }
+}
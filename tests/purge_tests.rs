@@ -0,0 +1,151 @@
+mod common;
+
+use cache_sniper::cdn::CdnRegistry;
+use cache_sniper::error::ErrorKind;
+use cache_sniper::http_client::SniperClient;
+use cache_sniper::purge::{self, ObjectSnapshot, PollPlan, PurgeReport, PurgeStep};
+use cache_sniper::verdict::CacheStatus;
+use common::headers;
+use reqwest::header::HeaderMap;
+use reqwest::Method;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use warp::http::Response;
+use warp::Filter;
+
+fn snapshot(etag: &str, body: &str, age: &str, x_cache: &str) -> ObjectSnapshot {
+    snapshot_with_status(200, etag, body, age, x_cache)
+}
+
+fn snapshot_with_status(status: u16, etag: &str, body: &str, age: &str, x_cache: &str) -> ObjectSnapshot {
+    let h = headers(&[("etag", etag), ("age", age), ("x-cache", x_cache)]);
+    ObjectSnapshot::capture(status, &h, body, &CdnRegistry::default(), false)
+}
+
+#[cfg(test)]
+mod snapshot_tests {
+    use super::*;
+
+    #[test]
+    fn test_capture() {
+        let snap = snapshot("\"v1\"", "hello", "30", "HIT");
+
+        assert_eq!(snap.etag.as_deref(), Some("\"v1\""));
+        assert_eq!(snap.age, Some(30));
+        assert_eq!(snap.cache_status, CacheStatus::Hit);
+        assert_eq!(snap.body_hash, snapshot("\"v1\"", "hello", "0", "MISS").body_hash);
+    }
+
+    #[test]
+    fn test_same_object_growing_older_is_not_fresh() {
+        let before = snapshot("\"v1\"", "hello", "30", "HIT");
+
+        assert_eq!(snapshot("\"v1\"", "hello", "35", "HIT").invalidated_since(&before), None);
+    }
+
+    #[test]
+    fn test_fresh_object_evidence() {
+        let before = snapshot("\"v1\"", "hello", "30", "HIT");
+
+        assert_eq!(snapshot("\"v2\"", "hello", "30", "HIT").invalidated_since(&before).as_deref(), Some("ETag changed"));
+        assert_eq!(snapshot("\"v1\"", "bye", "30", "HIT").invalidated_since(&before).as_deref(), Some("body changed"));
+        assert_eq!(snapshot("\"v1\"", "hello", "2", "HIT").invalidated_since(&before).as_deref(), Some("Age dropped from 30 to 2"));
+        assert_eq!(snapshot("\"v1\"", "hello", "30", "MISS").invalidated_since(&before).as_deref(), Some("edge answered MISS"));
+    }
+
+    #[test]
+    fn test_error_responses_are_not_fresh_objects() {
+        let before = snapshot("\"v1\"", "hello", "30", "HIT");
+
+        assert_eq!(snapshot_with_status(503, "\"err\"", "Service Unavailable", "0", "MISS").invalidated_since(&before), None);
+        assert_eq!(snapshot_with_status(206, "\"v2\"", "hel", "0", "MISS").invalidated_since(&before), None);
+        let error_before = snapshot_with_status(404, "\"v1\"", "missing", "30", "HIT");
+        assert_eq!(snapshot_with_status(404, "\"v2\"", "gone", "0", "MISS").invalidated_since(&error_before), None);
+    }
+
+    #[test]
+    fn test_parse_method_and_display() {
+        let method = purge::parse_method("ban").unwrap();
+        assert_eq!(method.as_str(), "BAN");
+        assert!(purge::parse_method("not a method").is_err());
+
+        let step = PurgeStep::Request { method, url: Some("http://varnish/ban".to_string()) };
+        assert_eq!(step.to_string(), "BAN http://varnish/ban");
+        assert_eq!(PurgeStep::Command("purge.sh".to_string()).to_string(), "`purge.sh`");
+    }
+
+    #[test]
+    fn test_report_without_edges_is_not_invalidated() {
+        let report = PurgeReport::new("http://a/", &PurgeStep::Command("true".to_string()), "exit status: 0".to_string(), vec![]);
+
+        assert!(!report.invalidated);
+        assert_eq!(report.time_to_invalidate_ms, None);
+    }
+}
+
+#[cfg(test)]
+mod verify_tests {
+    use super::*;
+
+    /// Serves a cached v1 until it receives a PURGE, then a fresh v2
+    async fn purgeable_origin() -> String {
+        let purged = Arc::new(AtomicBool::new(false));
+        let route = warp::method().map(move |method: Method| {
+            if method.as_str() == "PURGE" {
+                purged.store(true, Ordering::SeqCst);
+                return Response::builder().body(String::new()).unwrap();
+            }
+            let (etag, x_cache, age) = if purged.load(Ordering::SeqCst) { ("\"v2\"", "MISS", "0") } else { ("\"v1\"", "HIT", "120") };
+            Response::builder().header("etag", etag).header("x-cache", x_cache).header("age", age).body(etag.to_string()).unwrap()
+        });
+        let (addr, server) = warp::serve(route).bind_ephemeral(([127, 0, 0, 1], 0));
+        tokio::spawn(server);
+        format!("http://{}/", addr)
+    }
+
+    fn plan(deadline_ms: u64) -> PollPlan {
+        PollPlan { interval: Duration::from_millis(20), deadline: Duration::from_millis(deadline_ms) }
+    }
+
+    #[tokio::test]
+    async fn test_purge_request_invalidates() {
+        let url = purgeable_origin().await;
+        let step = PurgeStep::Request { method: purge::parse_method("PURGE").unwrap(), url: None };
+        let report = purge::purge_verify(&url, &SniperClient::default(), &HeaderMap::new(), &CdnRegistry::default(), None, &step, plan(2000))
+            .await
+            .unwrap();
+
+        assert_eq!(report.purge_outcome, "HTTP 200");
+        assert!(report.invalidated);
+        assert_eq!(report.edges.len(), 1);
+        assert_eq!(report.edges[0].polls, 1);
+        assert_eq!(report.edges[0].reason.as_deref(), Some("ETag changed"));
+        assert_eq!(report.edges[0].after.as_ref().unwrap().etag.as_deref(), Some("\"v2\""));
+    }
+
+    #[tokio::test]
+    async fn test_no_op_purge_times_out() {
+        let url = purgeable_origin().await;
+        let step = PurgeStep::Command("test -n \"$CACHE_SNIPER_URL\"".to_string());
+        let report = purge::purge_verify(&url, &SniperClient::default(), &HeaderMap::new(), &CdnRegistry::default(), None, &step, plan(100))
+            .await
+            .unwrap();
+
+        assert!(!report.invalidated);
+        assert!(report.edges[0].polls > 1);
+        assert_eq!(report.edges[0].reason, None);
+    }
+
+    #[tokio::test]
+    async fn test_failing_purge_command_is_an_error() {
+        let url = purgeable_origin().await;
+        let step = PurgeStep::Command("echo denied >&2; exit 3".to_string());
+        let error = purge::purge_verify(&url, &SniperClient::default(), &HeaderMap::new(), &CdnRegistry::default(), None, &step, plan(100))
+            .await
+            .unwrap_err();
+
+        assert_eq!(error.kind, ErrorKind::Other);
+        assert!(error.message.contains("denied"));
+    }
+}
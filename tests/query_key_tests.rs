@@ -0,0 +1,144 @@
+mod common;
+
+use cache_sniper::cdn::CdnRegistry;
+use cache_sniper::http_client::{RequestMethod, SniperClient};
+use cache_sniper::query_key::{self, CacheObservation, KeyFinding, QueryKeyAnalysis, QueryProbe, QueryVariant};
+use cache_sniper::verdict::CacheStatus;
+use common::headers;
+use reqwest::header::HeaderMap;
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use warp::http::Response;
+use warp::Filter;
+
+fn observation(status: CacheStatus, age: Option<u64>, etag: Option<&str>) -> CacheObservation {
+    CacheObservation { status, age, etag: etag.map(str::to_string) }
+}
+
+fn probe(variant: QueryVariant, shares_entry: Option<bool>) -> QueryProbe {
+    QueryProbe {
+        variant,
+        url: "https://example.com/?x=1".to_string(),
+        reference_url: "https://example.com/".to_string(),
+        observation: observation(CacheStatus::Hit, None, None),
+        shares_entry,
+    }
+}
+
+#[cfg(test)]
+mod variant_tests {
+    use super::*;
+
+    #[test]
+    fn test_variants_without_query() {
+        let variants = query_key::variant_urls("https://example.com/app.js", "n1").unwrap();
+
+        assert_eq!(variants[0], (QueryVariant::Random, "https://example.com/app.js?cache_sniper_bust=n1".to_string(), None));
+        assert_eq!(
+            variants[1],
+            (
+                QueryVariant::Reordered,
+                "https://example.com/app.js?cache_sniper_b=n1&cache_sniper_a=n1".to_string(),
+                Some("https://example.com/app.js?cache_sniper_a=n1&cache_sniper_b=n1".to_string())
+            )
+        );
+        assert_eq!(variants[2].1, "https://example.com/app.js?utm_source=cache_sniper&utm_medium=probe&utm_campaign=n1");
+    }
+
+    #[test]
+    fn test_existing_parameters_are_reversed() {
+        let variants = query_key::variant_urls("https://example.com/search?q=shoes&page=2", "n1").unwrap();
+
+        assert_eq!(variants[0].1, "https://example.com/search?q=shoes&page=2&cache_sniper_bust=n1");
+        assert_eq!(variants[1], (QueryVariant::Reordered, "https://example.com/search?page=2&q=shoes".to_string(), None));
+    }
+}
+
+#[cfg(test)]
+mod comparison_tests {
+    use super::*;
+
+    #[test]
+    fn test_shares_entry_with() {
+        let reference = observation(CacheStatus::Hit, Some(100), Some("\"v1\""));
+
+        assert_eq!(observation(CacheStatus::Hit, Some(101), Some("\"v1\"")).shares_entry_with(&reference), Some(true));
+        assert_eq!(observation(CacheStatus::Miss, None, Some("\"v1\"")).shares_entry_with(&reference), Some(false));
+        assert_eq!(observation(CacheStatus::Hit, Some(3), Some("\"v1\"")).shares_entry_with(&reference), Some(false));
+        assert_eq!(observation(CacheStatus::Hit, Some(101), Some("\"v2\"")).shares_entry_with(&reference), Some(false));
+    }
+
+    #[test]
+    fn test_uncached_reference_is_inconclusive() {
+        let reference = observation(CacheStatus::Miss, None, None);
+
+        assert_eq!(observation(CacheStatus::Miss, None, None).shares_entry_with(&reference), None);
+    }
+
+    #[test]
+    fn test_findings() {
+        let baseline = || observation(CacheStatus::Hit, Some(10), None);
+        let finding = |probes| QueryKeyAnalysis::summarize(baseline(), probes, vec![]).query_in_cache_key;
+
+        assert_eq!(finding(vec![probe(QueryVariant::Random, Some(false)), probe(QueryVariant::Utm, Some(false))]), KeyFinding::Yes);
+        assert_eq!(finding(vec![probe(QueryVariant::Random, Some(true)), probe(QueryVariant::Utm, Some(true))]), KeyFinding::No);
+        assert_eq!(finding(vec![probe(QueryVariant::Random, Some(false)), probe(QueryVariant::Utm, Some(true))]), KeyFinding::Partial);
+        assert_eq!(finding(vec![probe(QueryVariant::Random, None)]), KeyFinding::Unknown);
+    }
+
+    #[test]
+    fn test_warnings() {
+        let analysis = QueryKeyAnalysis::summarize(
+            observation(CacheStatus::Hit, None, None),
+            vec![probe(QueryVariant::Random, Some(true)), probe(QueryVariant::Utm, Some(false))],
+            vec![],
+        );
+
+        assert_eq!(analysis.warnings.len(), 2);
+        assert!(analysis.warnings[0].contains("cache busters"));
+        assert!(analysis.warnings[1].contains("UTM"));
+    }
+
+    #[test]
+    fn test_observation_from_headers() {
+        let h = headers(&[("cf-cache-status", "HIT"), ("age", "12"), ("etag", "\"v1\"")]);
+
+        assert_eq!(CacheObservation::from_headers(&h, &CdnRegistry::default(), false), observation(CacheStatus::Hit, Some(12), Some("\"v1\"")));
+    }
+}
+
+#[cfg(test)]
+mod probe_tests {
+    use super::*;
+
+    /// A cache that drops utm_* parameters and sorts the rest before keying
+    async fn normalizing_cache() -> String {
+        let seen = Arc::new(Mutex::new(HashSet::new()));
+        let route = warp::query::raw().or(warp::any().map(String::new)).unify().map(move |query: String| {
+            let mut params = query.split('&').filter(|p| !p.is_empty() && !p.starts_with("utm_")).collect::<Vec<_>>();
+            params.sort();
+            let hit = !seen.lock().unwrap().insert(params.join("&"));
+            Response::builder().header("x-cache", if hit { "HIT" } else { "MISS" }).body("ok").unwrap()
+        });
+        let (addr, server) = warp::serve(route).bind_ephemeral(([127, 0, 0, 1], 0));
+        tokio::spawn(server);
+        format!("http://{}/page", addr)
+    }
+
+    #[tokio::test]
+    async fn test_partial_query_key() {
+        let url = normalizing_cache().await;
+        let (client, registry) = (SniperClient::default(), CdnRegistry::default());
+        client.fetch_headers(&url, RequestMethod::Get, &HeaderMap::new()).await.unwrap();
+
+        let analysis = query_key::probe_query_key(&url, &client, RequestMethod::Get, &HeaderMap::new(), &registry).await.unwrap();
+        let shared = analysis.probes.iter().map(|p| (p.variant, p.shares_entry)).collect::<Vec<_>>();
+
+        assert_eq!(analysis.baseline.status, CacheStatus::Hit);
+        assert_eq!(
+            shared,
+            vec![(QueryVariant::Random, Some(false)), (QueryVariant::Reordered, Some(true)), (QueryVariant::Utm, Some(true))]
+        );
+        assert_eq!(analysis.query_in_cache_key, KeyFinding::Partial);
+    }
+}
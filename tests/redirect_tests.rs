@@ -0,0 +1,75 @@
+mod common;
+
+use cache_sniper::cdn::CdnRegistry;
+use cache_sniper::redirects::RedirectHop;
+use cache_sniper::verdict::CacheStatus;
+use common::headers;
+
+#[cfg(test)]
+mod redirect_hop_tests {
+    use super::*;
+
+    #[test]
+    fn test_cached_permanent_redirect() {
+        let h = headers(&[
+            ("location", "https://example.com/new"),
+            ("cache-control", "public, max-age=86400"),
+            ("cf-cache-status", "HIT"),
+        ]);
+        let hop = RedirectHop::analyze("https://example.com/old", 301, &h, &CdnRegistry::default(), false);
+
+        assert_eq!(hop.location.as_deref(), Some("https://example.com/new"));
+        assert_eq!(hop.cache_status, Some(CacheStatus::Hit));
+        assert!(hop.shared_cache.cacheable);
+        assert!(hop.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_no_store_redirect_is_flagged() {
+        let h = headers(&[("location", "/login"), ("cache-control", "no-store")]);
+        let hop = RedirectHop::analyze("https://example.com/", 302, &h, &CdnRegistry::default(), false);
+
+        assert_eq!(hop.warnings.len(), 1);
+        assert!(hop.warnings[0].contains("no-store"));
+    }
+
+    #[test]
+    fn test_uncacheable_redirect_is_flagged() {
+        let h = headers(&[("location", "/en/"), ("cache-control", "private")]);
+        let hop = RedirectHop::analyze("https://example.com/", 307, &h, &CdnRegistry::default(), false);
+
+        assert!(!hop.shared_cache.cacheable);
+        assert!(hop.warnings[0].contains("not cacheable by shared caches"));
+    }
+
+    #[test]
+    fn test_permanent_redirect_without_cache_control_is_heuristically_cacheable() {
+        let h = headers(&[("location", "https://example.com/new")]);
+
+        for status in [301, 308] {
+            let hop = RedirectHop::analyze("https://example.com/old", status, &h, &CdnRegistry::default(), false);
+            assert!(hop.shared_cache.cacheable, "{}", status);
+            assert!(hop.warnings.is_empty());
+        }
+        let hop = RedirectHop::analyze("https://example.com/old", 302, &h, &CdnRegistry::default(), false);
+        assert!(!hop.shared_cache.cacheable);
+    }
+
+    #[test]
+    fn test_authorization_makes_redirect_uncacheable() {
+        let h = headers(&[("location", "/account"), ("cache-control", "max-age=600")]);
+        let hop = RedirectHop::analyze("https://example.com/", 301, &h, &CdnRegistry::default(), true);
+
+        assert!(!hop.shared_cache.cacheable);
+        assert!(hop.warnings[0].contains("Authorization"));
+    }
+
+    #[test]
+    fn test_final_response_is_not_flagged() {
+        let h = headers(&[("cache-control", "no-store")]);
+        let hop = RedirectHop::analyze("https://example.com/en/", 200, &h, &CdnRegistry::default(), false);
+
+        assert!(hop.warnings.is_empty());
+        assert_eq!(hop.cache_status, Some(CacheStatus::Uncacheable));
+    }
+}
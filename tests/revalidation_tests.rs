@@ -0,0 +1,105 @@
+mod common;
+
+use cache_sniper::http_client::SniperClient;
+use cache_sniper::revalidation::{self, ConditionalCheck, EtagStrength, RevalidationReport};
+use cache_sniper::validate_cache::validate_cache;
+use common::headers;
+use reqwest::header::HeaderMap;
+use warp::http::{Response, StatusCode};
+use warp::Filter;
+
+#[cfg(test)]
+mod validator_tests {
+    use super::*;
+
+    #[test]
+    fn test_etag_strength() {
+        assert_eq!(EtagStrength::of("\"abc\""), EtagStrength::Strong);
+        assert_eq!(EtagStrength::of("W/\"abc\""), EtagStrength::Weak);
+        assert_eq!(revalidation::flip_strength("\"abc\""), "W/\"abc\"");
+        assert_eq!(revalidation::flip_strength("W/\"abc\""), "\"abc\"");
+    }
+
+    #[test]
+    fn test_weak_comparison() {
+        assert!(revalidation::weak_match("\"abc\"", "\"abc\""));
+        assert!(revalidation::weak_match("W/\"abc\"", "\"abc\""));
+        assert!(revalidation::weak_match("W/\"abc\"", " W/\"abc\""));
+        assert!(!revalidation::weak_match("W/\"abc\"", "\"abd\""));
+    }
+
+    #[test]
+    fn test_304_for_another_tag_fails_the_check() {
+        let not_modified = headers(&[("etag", "\"v2\"")]);
+        let check = ConditionalCheck::evaluate("if-none-match", "W/\"v1\"", 304, 0, &HeaderMap::new(), &not_modified);
+
+        assert!(!check.passed);
+    }
+
+    #[test]
+    fn test_304_missing_required_headers() {
+        let full = headers(&[("etag", "\"v1\""), ("cache-control", "max-age=60"), ("vary", "Accept-Encoding"), ("content-type", "text/html")]);
+        let not_modified = headers(&[("etag", "\"v1\"")]);
+        let check = ConditionalCheck::evaluate("if-none-match", "\"v1\"", 304, 0, &full, &not_modified);
+
+        assert!(check.passed);
+        assert_eq!(check.missing_headers, vec!["cache-control", "vary"]);
+        assert!(!RevalidationReport::new(Some(EtagStrength::Strong), Some(check), None, None).honors_validators);
+    }
+
+    #[test]
+    fn test_full_response_fails_the_check() {
+        let check = ConditionalCheck::evaluate("if-modified-since", "Thu, 11 Jan 2024 10:00:00 GMT", 200, 512, &HeaderMap::new(), &HeaderMap::new());
+
+        assert!(!check.passed);
+        assert!(check.missing_headers.is_empty());
+    }
+
+    #[test]
+    fn test_no_validators_means_nothing_honored() {
+        assert!(!RevalidationReport::new(None, None, None, None).honors_validators);
+    }
+}
+
+#[cfg(test)]
+mod conditional_request_tests {
+    use super::*;
+
+    async fn origin(honor_validators: bool) -> String {
+        let route = warp::header::optional::<String>("if-none-match").map(move |inm: Option<String>| {
+            let matches = inm.is_some_and(|v| revalidation::weak_match(&v, "\"v1\""));
+            let builder = Response::builder().header("etag", "\"v1\"").header("cache-control", "max-age=60");
+            if honor_validators && matches {
+                builder.status(StatusCode::NOT_MODIFIED).body(String::new()).unwrap()
+            } else {
+                builder.body("hello".to_string()).unwrap()
+            }
+        });
+        let (addr, server) = warp::serve(route).bind_ephemeral(([127, 0, 0, 1], 0));
+        tokio::spawn(server);
+        format!("http://{}/", addr)
+    }
+
+    #[tokio::test]
+    async fn test_server_honoring_if_none_match() {
+        let url = origin(true).await;
+        let result = validate_cache(&SniperClient::default(), &url, &HeaderMap::new(), true).await.unwrap();
+        let json = serde_json::to_value(result).unwrap();
+
+        assert_eq!(json["revalidation"]["etag_strength"], "strong");
+        assert_eq!(json["revalidation"]["if_none_match"]["status"], 304);
+        assert_eq!(json["revalidation"]["weak_comparison"]["sent"], "W/\"v1\"");
+        assert_eq!(json["revalidation"]["honors_validators"], true);
+    }
+
+    #[tokio::test]
+    async fn test_server_ignoring_validators() {
+        let url = origin(false).await;
+        let result = validate_cache(&SniperClient::default(), &url, &HeaderMap::new(), true).await.unwrap();
+        let json = serde_json::to_value(result).unwrap();
+
+        assert_eq!(json["revalidation"]["if_none_match"]["status"], 200);
+        assert_eq!(json["revalidation"]["if_none_match"]["body_bytes"], 5);
+        assert_eq!(json["revalidation"]["honors_validators"], false);
+    }
+}
@@ -0,0 +1,150 @@
+use cache_sniper::cache_control::CacheDirectives;
+use cache_sniper::cdn::CdnRegistry;
+use cache_sniper::http_client::SniperClient;
+use cache_sniper::stale::{self, Expectation, StaleWindows, VERSION_HEADER};
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::net::{SocketAddr, TcpListener};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use warp::http::{Response, StatusCode};
+use warp::Filter;
+
+const WINDOWS: StaleWindows = StaleWindows { max_age: 1, stale_while_revalidate: 2, stale_if_error: 2 };
+
+fn free_addr() -> SocketAddr {
+    TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap()
+}
+
+#[cfg(test)]
+mod window_tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_control_per_scenario() {
+        assert_eq!(WINDOWS.cache_control("/swr-1"), "public, max-age=1, stale-while-revalidate=2");
+        assert_eq!(WINDOWS.cache_control("/sie-1"), "public, max-age=1, stale-if-error=2");
+    }
+
+    #[test]
+    fn test_windows_too_short() {
+        assert!(WINDOWS.validate().is_ok());
+        assert!(StaleWindows { stale_if_error: 1, ..WINDOWS }.validate().is_err());
+        assert!(StaleWindows { max_age: 0, ..WINDOWS }.validate().is_err());
+    }
+
+    #[test]
+    fn test_expectations() {
+        assert!(Expectation::Version(2).met_by(200, Some(2)));
+        assert!(!Expectation::Version(2).met_by(200, Some(3)));
+        assert!(!Expectation::Version(2).met_by(503, None));
+        assert!(Expectation::OriginError.met_by(503, None));
+        assert!(!Expectation::OriginError.met_by(200, Some(1)));
+    }
+}
+
+#[cfg(test)]
+mod scenario_tests {
+    use super::*;
+
+    #[derive(Clone)]
+    struct Entry {
+        version: String,
+        stored: Instant,
+        directives: CacheDirectives,
+    }
+
+    impl Entry {
+        fn age(&self) -> u64 {
+            self.stored.elapsed().as_secs()
+        }
+
+        fn max_age(&self) -> u64 {
+            self.directives.max_age.unwrap_or(0)
+        }
+
+        fn respond(&self, x_cache: &str) -> Response<String> {
+            Response::builder()
+                .header(VERSION_HEADER, &self.version)
+                .header("age", self.age())
+                .header("x-cache", x_cache)
+                .body(String::new())
+                .unwrap()
+        }
+    }
+
+    async fn fetch(origin: SocketAddr, path: &str) -> Option<Entry> {
+        let response = reqwest::get(format!("http://{}{}", origin, path)).await.ok()?;
+        if !response.status().is_success() {
+            return None;
+        }
+        let header = |name| response.headers().get(name).and_then(|v| v.to_str().ok()).unwrap_or("").to_string();
+        Some(Entry { version: header(VERSION_HEADER), stored: Instant::now(), directives: CacheDirectives::parse(&header("cache-control")) })
+    }
+
+    /// Minimal shared cache honoring stale-while-revalidate and stale-if-error
+    async fn caching_proxy(origin: SocketAddr) -> String {
+        let cache: Arc<Mutex<HashMap<String, Entry>>> = Arc::default();
+        let route = warp::path::full().and_then(move |path: warp::path::FullPath| {
+            let (cache, path) = (cache.clone(), path.as_str().to_string());
+            async move {
+                let cached = cache.lock().unwrap().get(&path).cloned();
+                if let Some(entry) = &cached {
+                    if entry.age() < entry.max_age() {
+                        return Ok::<_, Infallible>(entry.respond("HIT"));
+                    }
+                    if entry.age() < entry.max_age() + entry.directives.stale_while_revalidate.unwrap_or(0) {
+                        let (cache, path) = (cache.clone(), path.clone());
+                        tokio::spawn(async move {
+                            if let Some(fresh) = fetch(origin, &path).await {
+                                cache.lock().unwrap().insert(path, fresh);
+                            }
+                        });
+                        return Ok(entry.respond("STALE"));
+                    }
+                }
+                match (fetch(origin, &path).await, cached) {
+                    (Some(fresh), _) => {
+                        cache.lock().unwrap().insert(path, fresh.clone());
+                        Ok(fresh.respond("MISS"))
+                    }
+                    (None, Some(entry)) if entry.age() < entry.max_age() + entry.directives.stale_if_error.unwrap_or(0) => {
+                        Ok(entry.respond("STALE"))
+                    }
+                    (None, _) => Ok(Response::builder().status(StatusCode::SERVICE_UNAVAILABLE).body(String::new()).unwrap()),
+                }
+            }
+        });
+        let (addr, server) = warp::serve(route).bind_ephemeral(([127, 0, 0, 1], 0));
+        tokio::spawn(server);
+        format!("http://{}/", addr)
+    }
+
+    #[tokio::test]
+    async fn test_cache_honoring_stale_directives_passes() {
+        let origin = free_addr();
+        let cache_url = caching_proxy(origin).await;
+        let report = stale::stale_check(&cache_url, &SniperClient::default(), &CdnRegistry::default(), origin, WINDOWS)
+            .await
+            .unwrap();
+
+        assert!(report.scenarios.iter().all(|s| s.passed), "{:#?}", report.scenarios);
+        assert!(report.passed);
+        let swr = &report.scenarios[0];
+        assert_eq!(swr.steps[1].version, Some(swr.steps[0].version.unwrap()));
+        assert!(swr.steps[2].origin_requests >= 1, "the stale response triggers a background fetch");
+        assert_eq!(report.scenarios[1].steps[2].http_status, Some(503));
+    }
+
+    #[tokio::test]
+    async fn test_no_cache_in_front_fails() {
+        let origin = free_addr();
+        let report = stale::stale_check(&format!("http://{}/", origin), &SniperClient::default(), &CdnRegistry::default(), origin, WINDOWS)
+            .await
+            .unwrap();
+
+        assert!(!report.passed);
+        assert!(!report.scenarios[0].steps[1].passed);
+        assert!(!report.scenarios[1].steps[1].passed);
+    }
+}
@@ -0,0 +1,71 @@
+use cache_sniper::http_client::{RequestMethod, SniperClient};
+use cache_sniper::timing::{self, ConnectionTimings, Timings};
+use reqwest::header::HeaderMap;
+use std::time::Duration;
+use warp::Filter;
+
+#[cfg(test)]
+mod summary_tests {
+    use super::*;
+
+    #[test]
+    fn test_summary_lists_measured_phases() {
+        let connection = ConnectionTimings { dns_ms: Some(2.4), connect_ms: Some(11.0), tls_ms: None };
+        let timings = Timings::new(connection, Duration::from_millis(80), Duration::from_millis(96));
+
+        assert_eq!(timings.ttfb_ms, 80.0);
+        assert_eq!(timings.summary(), "TTFB 80 ms, total 96 ms (probe connection: DNS 2 ms, connect 11 ms)");
+    }
+
+    #[test]
+    fn test_summary_without_probe() {
+        let timings = Timings::new(ConnectionTimings::default(), Duration::from_millis(80), Duration::from_millis(96));
+        assert_eq!(timings.summary(), "TTFB 80 ms, total 96 ms");
+    }
+}
+
+#[cfg(test)]
+mod probe_tests {
+    use super::*;
+
+    async fn local_server() -> std::net::SocketAddr {
+        let route = warp::any().map(|| "x".repeat(64 * 1024));
+        let (addr, server) = warp::serve(route).bind_ephemeral(([127, 0, 0, 1], 0));
+        tokio::spawn(server);
+        addr
+    }
+
+    #[tokio::test]
+    async fn test_ip_connect_host_skips_dns() {
+        let addr = local_server().await;
+        let timings = timing::probe("example.test", "127.0.0.1", addr.port(), false, Duration::from_secs(2)).await;
+
+        assert_eq!(timings.dns_ms, None);
+        assert!(timings.connect_ms.is_some());
+        assert_eq!(timings.tls_ms, None);
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_host_leaves_phases_empty() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+
+        let timings = timing::probe("127.0.0.1", "127.0.0.1", port, false, Duration::from_secs(2)).await;
+        assert_eq!(timings, ConnectionTimings::default());
+    }
+
+    #[tokio::test]
+    async fn test_timed_fetch_reads_the_body() {
+        let addr = local_server().await;
+        let url = format!("http://{}/", addr);
+        let client = SniperClient::default();
+
+        let fetched = client.fetch_timed(&url, RequestMethod::Get, &HeaderMap::new()).await.unwrap();
+        assert!(fetched.completed_time >= fetched.response_time);
+
+        let connection = client.connection_timings(&url).await;
+        assert_eq!(connection.dns_ms, None);
+        assert!(connection.connect_ms.is_some());
+    }
+}
@@ -0,0 +1,77 @@
+mod common;
+
+use cache_sniper::cache_checker::observed_verdict;
+use cache_sniper::cdn::CdnRegistry;
+use cache_sniper::vary::{self, VaryAnalysis};
+use cache_sniper::verdict::CacheStatus;
+use common::headers;
+
+#[cfg(test)]
+mod vary_parsing_tests {
+    use super::*;
+
+    #[test]
+    fn test_fields_are_normalized_and_deduplicated() {
+        let h = headers(&[("vary", "Accept-Encoding, Origin"), ("vary", "accept-encoding")]);
+        let analysis = VaryAnalysis::from_headers(&h).unwrap();
+
+        assert_eq!(analysis.fields, vec!["accept-encoding", "origin"]);
+        assert!(analysis.high_cardinality.is_empty());
+        assert!(analysis.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_high_cardinality_fields_are_flagged() {
+        let analysis = VaryAnalysis::parse("Accept-Encoding, User-Agent, Cookie");
+
+        assert_eq!(analysis.high_cardinality, vec!["user-agent", "cookie"]);
+        assert_eq!(analysis.warnings.len(), 2);
+        assert!(analysis.warnings[0].contains("User-Agent"));
+    }
+
+    #[test]
+    fn test_vary_star_is_flagged_but_not_probed() {
+        let analysis = VaryAnalysis::parse("*, Authorization");
+
+        assert_eq!(analysis.high_cardinality, vec!["*", "authorization"]);
+        assert_eq!(analysis.probe_fields().collect::<Vec<_>>(), vec!["authorization"]);
+    }
+
+    #[test]
+    fn test_missing_vary_header() {
+        assert!(VaryAnalysis::from_headers(&headers(&[("cache-control", "max-age=60")])).is_none());
+    }
+}
+
+#[cfg(test)]
+mod vary_probe_tests {
+    use super::*;
+
+    #[test]
+    fn test_probe_outcome_against_cached_baseline() {
+        assert_eq!(vary::keyed(CacheStatus::Hit, CacheStatus::Miss), Some(true));
+        assert_eq!(vary::keyed(CacheStatus::Hit, CacheStatus::Hit), Some(false));
+        assert_eq!(vary::keyed(CacheStatus::Hit, CacheStatus::Bypass), None);
+        assert_eq!(vary::keyed(CacheStatus::Miss, CacheStatus::Miss), None);
+    }
+
+    #[test]
+    fn test_observed_verdict_uses_reported_status_only() {
+        let registry = CdnRegistry::default();
+
+        let hit = observed_verdict(&headers(&[("cf-cache-status", "HIT"), ("cf-ray", "8a1b-LHR")]), &registry, false);
+        assert_eq!(hit.map(|v| v.status), Some(CacheStatus::Hit));
+
+        let no_status = observed_verdict(&headers(&[("cache-control", "public, max-age=600")]), &registry, false);
+        assert!(no_status.is_none());
+    }
+
+    #[test]
+    fn test_observed_verdict_accounts_for_authorization() {
+        let registry = CdnRegistry::default();
+        let h = headers(&[("cache-control", "max-age=600")]);
+
+        assert!(observed_verdict(&h, &registry, false).is_none());
+        assert_eq!(observed_verdict(&h, &registry, true).map(|v| v.status), Some(CacheStatus::Private));
+    }
+}
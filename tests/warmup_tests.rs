@@ -0,0 +1,104 @@
+mod common;
+
+use cache_sniper::cdn::CdnRegistry;
+use cache_sniper::http_client::{RequestMethod, SniperClient};
+use cache_sniper::verdict::CacheStatus;
+use cache_sniper::warmup::{self, WarmupPlan, WarmupReport, WarmupSample};
+use common::headers;
+use reqwest::header::HeaderMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use warp::http::Response;
+use warp::Filter;
+
+fn sample(request: u32, status: &str, age: Option<&str>) -> WarmupSample {
+    let mut h = headers(&[("x-cache", status)]);
+    if let Some(age) = age {
+        h.insert("age", age.parse().unwrap());
+    }
+    WarmupSample::from_response(request, 200, &h, &CdnRegistry::default(), false, Duration::from_millis(20))
+}
+
+#[cfg(test)]
+mod convergence_tests {
+    use super::*;
+
+    #[test]
+    fn test_hit_on_second_request() {
+        let report = WarmupReport::summarize(vec![
+            sample(1, "MISS", None),
+            sample(2, "HIT", Some("1")),
+            sample(3, "HIT", Some("2")),
+        ]);
+
+        assert_eq!(report.samples[1].cache_status, CacheStatus::Hit);
+        assert_eq!(report.samples[1].age, Some(1));
+        assert_eq!(report.requests_to_hit, Some(2));
+        assert!(report.age_monotonic);
+        assert!(!report.evicted);
+    }
+
+    #[test]
+    fn test_miss_after_hit_is_an_eviction() {
+        let report = WarmupReport::summarize(vec![sample(1, "HIT", Some("5")), sample(2, "MISS", None)]);
+
+        assert_eq!(report.requests_to_hit, Some(1));
+        assert!(report.evicted);
+    }
+
+    #[test]
+    fn test_age_reset_is_an_eviction() {
+        let report = WarmupReport::summarize(vec![sample(1, "HIT", Some("30")), sample(2, "HIT", Some("0"))]);
+
+        assert!(!report.age_monotonic);
+        assert!(report.evicted);
+    }
+
+    #[test]
+    fn test_never_a_hit() {
+        let report = WarmupReport::summarize(vec![sample(1, "MISS", None), sample(2, "MISS", None)]);
+
+        assert_eq!(report.requests_to_hit, None);
+        assert!(!report.evicted);
+    }
+}
+
+#[cfg(test)]
+mod probe_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_warm_up_against_a_cache_that_fills_on_second_request() {
+        let count = Arc::new(AtomicU32::new(0));
+        let route = warp::any().map(move || {
+            let seen = count.fetch_add(1, Ordering::SeqCst);
+            let builder = Response::builder().header("cache-control", "max-age=60");
+            match seen {
+                0 | 1 => builder.header("x-cache", "MISS"),
+                n => builder.header("x-cache", "HIT").header("age", (n - 2).to_string()),
+            }
+            .body("ok")
+            .unwrap()
+        });
+        let (addr, server) = warp::serve(route).bind_ephemeral(([127, 0, 0, 1], 0));
+        tokio::spawn(server);
+
+        let plan = WarmupPlan { requests: 4, delay: Duration::from_millis(10) };
+        let report = warmup::warm_up(
+            &format!("http://{}/", addr),
+            &SniperClient::default(),
+            RequestMethod::Get,
+            &HeaderMap::new(),
+            &CdnRegistry::default(),
+            plan,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(report.samples.len(), 4);
+        assert_eq!(report.requests_to_hit, Some(3));
+        assert!(report.age_monotonic);
+        assert!(!report.evicted);
+    }
+}